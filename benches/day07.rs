@@ -0,0 +1,63 @@
+//! Compare the day 7 solvers working on the rule map with the ones working on the interned `BagGraph`
+//!
+//! Run with `cargo bench --bench day07`.
+//! The generator and the solver are timed separately, as the `BagGraph` moves work from the solver into the generator.
+
+use advent_of_code_2020::*;
+use aoc_runner::{ArcStr, Runner};
+use std::error::Error;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+type SolverFactory = fn(ArcStr) -> Result<Box<dyn Runner>, Box<dyn Error>>;
+
+static SOLVERS: &[(&str, SolverFactory)] = &[
+    ("1", Factory::day7_part1),
+    ("1 (bag_graph)", Factory::day7_part1_bag_graph),
+    ("2 (naive)", Factory::day7_part2_naive),
+    ("2 (memoization)", Factory::day7_part2_memoization),
+    ("2 (bag_graph)", Factory::day7_part2_bag_graph),
+];
+
+/// Time spent measuring each generator and each solver
+const BUDGET: Duration = Duration::from_millis(500);
+
+/// Run `f` repeatedly for about [`BUDGET`] and return the median duration of a single run
+fn measure(mut f: impl FnMut()) -> Duration {
+    // The first run warms up the caches and estimates the number of iterations
+    let start = Instant::now();
+    f();
+    let estimate = start.elapsed().max(Duration::from_nanos(1));
+    let iterations = (BUDGET.as_nanos() / estimate.as_nanos()).clamp(10, 100_000) as usize;
+
+    let mut samples = (0..iterations)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .collect::<Vec<_>>();
+    samples.sort();
+    samples[samples.len() / 2]
+}
+
+fn main() {
+    let input = include_str!("../input/2020/day7.txt");
+    println!("Day 7, median of repeated runs");
+    println!("Part            | Answer | Generator |    Solver");
+    println!("----------------+--------+-----------+----------");
+    for (part, factory) in SOLVERS {
+        let generator = measure(|| {
+            black_box(factory(ArcStr::from(input)).unwrap());
+        });
+        let runner = factory(ArcStr::from(input)).unwrap();
+        let answer = runner.try_run().unwrap().to_string();
+        let solver = measure(|| {
+            black_box(runner.try_run().unwrap());
+        });
+        println!(
+            "{:<15} | {:<6} | {:>9.3?} | {:>9.3?}",
+            part, answer, generator, solver
+        );
+    }
+}
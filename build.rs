@@ -0,0 +1,66 @@
+//! Collect all `#[aoc(...)]` solvers, such that the `run_all` binary can run every registered solver.
+//!
+//! Solvers of days without an input file in `input/2020` are skipped.
+
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=input/2020");
+
+    let mut solvers = Vec::new();
+    for entry in fs::read_dir("src").unwrap() {
+        let path = entry.unwrap().path();
+        match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) if name.starts_with("day") && name.ends_with(".rs") => {}
+            _ => continue,
+        }
+
+        for line in fs::read_to_string(&path).unwrap().lines() {
+            let args = match line
+                .trim()
+                .strip_prefix("#[aoc(")
+                .and_then(|line| line.strip_suffix(")]"))
+            {
+                Some(args) => args,
+                None => continue,
+            };
+            let mut args = args.split(',').map(str::trim);
+            let day: u32 = args.next().unwrap()["day".len()..].parse().unwrap();
+            if !Path::new(&format!("input/2020/day{}.txt", day)).exists() {
+                continue;
+            }
+            let part: u32 = args.next().unwrap()["part".len()..].parse().unwrap();
+            let name = args.next().map(str::to_lowercase);
+            solvers.push((day, part, name));
+        }
+    }
+    solvers.sort();
+
+    let mut out = String::from("static SOLVERS: &[Solver] = &[\n");
+    for (day, part, name) in solvers {
+        let (ident, name) = match name {
+            Some(name) => (
+                format!("day{}_part{}_{}", day, part, name),
+                format!("Some({:?})", name),
+            ),
+            None => (format!("day{}_part{}", day, part), "None".to_string()),
+        };
+        writeln!(
+            out,
+            "    Solver {{ day: {day}, part: {part}, name: {name}, input: include_str!(concat!(env!(\"CARGO_MANIFEST_DIR\"), \"/input/2020/day{day}.txt\")), factory: Factory::{ident} }},",
+            day = day,
+            part = part,
+            name = name,
+            ident = ident,
+        )
+        .unwrap();
+    }
+    out.push_str("];\n");
+
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("solvers.rs");
+    fs::write(out_path, out).unwrap();
+}
@@ -0,0 +1,228 @@
+//! Combinatorics of joltage adapter chains
+//!
+//! A chain starts at the outlet with `0` jolts and ends at the device, which is rated `max_gap` jolts above the largest adapter.
+//! Each step may increase the joltage by `1..=max_gap`.
+//! Used for day 10.
+
+use crate::prelude::*;
+use num_bigint::BigUint;
+use std::fmt::{self, Display};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ChainError {
+    /// Two adapters have the same joltage, so they cannot be chained
+    Duplicate(u32),
+    /// No adapter bridges the joltages between `from` and `to`
+    Unreachable { from: u32, to: u32, max_gap: u32 },
+}
+
+impl Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainError::Duplicate(joltage) => {
+                write!(f, "multiple adapters are rated {} jolts", joltage)
+            }
+            ChainError::Unreachable { from, to, max_gap } => write!(
+                f,
+                "the device cannot be reached, the gap from {} to {} jolts is larger than {}",
+                from, to, max_gap
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ChainError {}
+
+#[derive(Clone, Debug)]
+pub struct AdapterChain {
+    /// Outlet, all adapters in ascending order, and the device
+    joltages: Vec<u32>,
+    max_gap: u32,
+    /// Number of arrangements from each joltage to the device
+    counts: Vec<BigUint>,
+}
+
+impl AdapterChain {
+    pub fn new(adapters: &[u32], max_gap: u32) -> Result<Self, ChainError> {
+        let mut joltages = Vec::with_capacity(adapters.len() + 2);
+        joltages.push(0);
+        joltages.extend(adapters.iter().copied().sorted());
+        joltages.push(joltages[joltages.len() - 1] + max_gap);
+
+        for (&from, &to) in joltages.iter().tuple_windows() {
+            if from == to {
+                return Err(ChainError::Duplicate(from));
+            }
+            if to - from > max_gap {
+                return Err(ChainError::Unreachable { from, to, max_gap });
+            }
+        }
+
+        // Dynamic programming from the device backwards
+        let mut counts = vec![BigUint::from(0_u32); joltages.len()];
+        counts[joltages.len() - 1] = BigUint::from(1_u32);
+        for i in (0..joltages.len() - 1).rev() {
+            let next = (i + 1..joltages.len())
+                .take_while(|&j| joltages[j] - joltages[i] <= max_gap)
+                .map(|j| &counts[j])
+                .sum();
+            counts[i] = next;
+        }
+
+        Ok(AdapterChain {
+            joltages,
+            max_gap,
+            counts,
+        })
+    }
+
+    /// How often each gap size occurs in the chain using all adapters
+    pub fn gap_histogram(&self) -> Map<u32, usize> {
+        let mut histogram = Map::new();
+        for (from, to) in self.joltages.iter().tuple_windows() {
+            *histogram.entry(to - from).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Number of distinct arrangements connecting the outlet to the device
+    pub fn count(&self) -> &BigUint {
+        &self.counts[0]
+    }
+
+    /// Indices of all joltages reachable in one step from `idx`
+    fn successors(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        let start = self.joltages[idx];
+        (idx + 1..self.joltages.len())
+            .take_while(move |&j| self.joltages[j] - start <= self.max_gap)
+    }
+
+    /// The `index`-th arrangement in lexicographic order, as the list of adapters used
+    ///
+    /// Returns `None` if there are not that many arrangements.
+    pub fn arrangement(&self, index: &BigUint) -> Option<Vec<u32>> {
+        if index >= self.count() {
+            return None;
+        }
+        let mut index = index.clone();
+        let device = self.joltages.len() - 1;
+        let mut pos = 0;
+        let mut adapters = Vec::new();
+        while pos != device {
+            pos = self
+                .successors(pos)
+                .find(|&next| {
+                    if index < self.counts[next] {
+                        true
+                    } else {
+                        index -= &self.counts[next];
+                        false
+                    }
+                })
+                .expect("The index is smaller than the number of arrangements");
+            if pos != device {
+                adapters.push(self.joltages[pos]);
+            }
+        }
+        Some(adapters)
+    }
+
+    /// Iterate over all arrangements in lexicographic order
+    pub fn arrangements(&self) -> impl Iterator<Item = Vec<u32>> + '_ {
+        let mut index = BigUint::from(0_u32);
+        std::iter::from_fn(move || {
+            let res = self.arrangement(&index)?;
+            index += 1_u32;
+            Some(res)
+        })
+    }
+
+    /// Pick an arrangement uniformly at random, deterministically derived from `seed`
+    pub fn sample(&self, seed: u64) -> Vec<u32> {
+        // Draw 64 more random bits than needed, such that the modulo bias is negligible
+        let mut state = seed;
+        let digits = (self.count().bits() / 32 + 3) as usize;
+        let random = (0..digits).map(|_| splitmix32(&mut state)).collect();
+        let index = BigUint::new(random) % self.count();
+        self.arrangement(&index).unwrap()
+    }
+}
+
+/// A small deterministic random number generator
+fn splitmix32(state: &mut u64) -> u32 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    ((z ^ (z >> 31)) >> 32) as u32
+}
+
+#[test]
+fn test_gap_histogram() {
+    let chain = AdapterChain::new(&[1, 2, 4, 8], 4).unwrap();
+    let expected: Map<_, _> = vec![(1, 2), (2, 1), (4, 2)].into_iter().collect();
+    assert_eq!(expected, chain.gap_histogram());
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        Err(ChainError::Unreachable {
+            from: 1,
+            to: 5,
+            max_gap: 3
+        }),
+        AdapterChain::new(&[1, 5], 3).map(|_| ())
+    );
+    assert_eq!(
+        Err(ChainError::Unreachable {
+            from: 0,
+            to: 2,
+            max_gap: 1
+        }),
+        AdapterChain::new(&[2], 1).map(|_| ())
+    );
+    assert_eq!(
+        Err(ChainError::Duplicate(2)),
+        AdapterChain::new(&[2, 1, 2], 3).map(|_| ())
+    );
+}
+
+#[test]
+fn test_big_count() {
+    // The tribonacci numbers, checked against a `u128` implementation
+    let adapters = (1..=100).collect_vec();
+    let mut counts = [1_u128, 0, 0];
+    for _ in 0..100 {
+        counts = [counts.iter().sum(), counts[0], counts[1]];
+    }
+    let chain = AdapterChain::new(&adapters, 3).unwrap();
+    assert_eq!(&BigUint::from(counts[0]), chain.count());
+
+    let adapters = (1..=1000).collect_vec();
+    let chain = AdapterChain::new(&adapters, 3).unwrap();
+    assert!(chain.count() > &BigUint::from(u128::MAX));
+}
+
+#[test]
+fn test_arrangements() {
+    let chain = AdapterChain::new(&[1, 2, 3, 5], 3).unwrap();
+    let all = chain.arrangements().collect_vec();
+    assert_eq!(
+        vec![
+            vec![1, 2, 3, 5],
+            vec![1, 2, 5],
+            vec![1, 3, 5],
+            vec![2, 3, 5],
+            vec![2, 5],
+            vec![3, 5],
+        ],
+        all
+    );
+    assert_eq!(&BigUint::from(all.len()), chain.count());
+    assert_eq!(None, chain.arrangement(&BigUint::from(6_u32)));
+
+    for seed in 0..20 {
+        assert!(all.contains(&chain.sample(seed)));
+    }
+}
@@ -0,0 +1,160 @@
+//! Record the generations of a simulation as frames and export them as a text log or an animated GIF
+//!
+//! Used for the seating of day 11 and the cubes of day 17.
+
+use crate::grid::{Grid, GridCell};
+use std::convert::TryFrom;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// A list of captioned frames, with a color for every char which may appear in them
+#[derive(Clone, Debug)]
+pub struct Animation {
+    palette: Vec<(char, [u8; 3])>,
+    frames: Vec<(String, Grid<char>)>,
+}
+
+impl Animation {
+    pub fn new(palette: &[(char, [u8; 3])]) -> Self {
+        assert!(
+            !palette.is_empty() && palette.len() <= 256,
+            "A GIF palette needs between 1 and 256 colors"
+        );
+        Animation {
+            palette: palette.to_vec(),
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn push<T: GridCell>(&mut self, caption: impl Into<String>, grid: &Grid<T>) {
+        let frame = Grid::from_fn(grid.width(), grid.height(), |pos| grid[pos].to_char());
+        if let Some(c) = frame.iter().find(|&&c| self.color_index(c).is_none()) {
+            panic!("The palette has no color for {:?}", c);
+        }
+        self.frames.push((caption.into(), frame));
+    }
+
+    fn color_index(&self, c: char) -> Option<u8> {
+        self.palette
+            .iter()
+            .position(|&(symbol, _)| symbol == c)
+            .map(|idx| idx as u8)
+    }
+
+    /// All frames as text, each preceded by its caption and followed by an empty line
+    pub fn text_log(&self) -> String {
+        let mut log = String::new();
+        for (caption, frame) in &self.frames {
+            writeln!(log, "{}\n{}", caption, frame).unwrap();
+        }
+        log
+    }
+
+    /// Encode all frames as an endlessly looping GIF
+    ///
+    /// Every cell is drawn as a `scale` x `scale` square and every frame is shown for `delay` hundredths of a second.
+    /// Smaller frames are drawn into the top-left corner of the largest one.
+    pub fn gif(&self, scale: usize, delay: u16) -> io::Result<Vec<u8>> {
+        let width = self
+            .frames
+            .iter()
+            .map(|(_, f)| f.width())
+            .max()
+            .unwrap_or(0)
+            * scale;
+        let height = self
+            .frames
+            .iter()
+            .map(|(_, f)| f.height())
+            .max()
+            .unwrap_or(0)
+            * scale;
+        let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "animation is too large");
+        let width_u16 = u16::try_from(width).map_err(|_| too_large())?;
+        let height_u16 = u16::try_from(height).map_err(|_| too_large())?;
+
+        let palette: Vec<u8> = self.palette.iter().flat_map(|(_, rgb)| *rgb).collect();
+        let mut out = Vec::new();
+        {
+            let mut encoder = gif::Encoder::new(&mut out, width_u16, height_u16, &palette)
+                .map_err(into_io_error)?;
+            encoder
+                .set_repeat(gif::Repeat::Infinite)
+                .map_err(into_io_error)?;
+            for (_, frame) in &self.frames {
+                let mut pixels = vec![0; width * height];
+                for (x, y) in frame.positions() {
+                    let color = self.color_index(frame[(x, y)]).unwrap();
+                    for row in &mut pixels[y * scale * width..][..scale * width].chunks_mut(width) {
+                        row[x * scale..][..scale].fill(color);
+                    }
+                }
+                let mut frame =
+                    gif::Frame::from_indexed_pixels(width_u16, height_u16, &pixels, None);
+                frame.delay = delay;
+                encoder.write_frame(&frame).map_err(into_io_error)?;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Write the text log to `<path>.txt` and the GIF to `<path>.gif` and return both paths
+    pub fn save(&self, path: &Path, scale: usize, delay: u16) -> io::Result<[PathBuf; 2]> {
+        let (text_path, gif_path) = (path.with_extension("txt"), path.with_extension("gif"));
+        std::fs::write(&text_path, self.text_log())?;
+        let mut file = BufWriter::new(File::create(&gif_path)?);
+        file.write_all(&self.gif(scale, delay)?)?;
+        file.flush()?;
+        Ok([text_path, gif_path])
+    }
+}
+
+fn into_io_error(err: gif::EncodingError) -> io::Error {
+    match err {
+        gif::EncodingError::Io(err) => err,
+        err => io::Error::new(io::ErrorKind::InvalidData, err),
+    }
+}
+
+#[cfg(test)]
+fn test_animation() -> Animation {
+    let mut animation = Animation::new(&[('.', [0, 0, 0]), ('#', [255, 255, 255])]);
+    let ctx = crate::parse::ParseContext::new(0, "");
+    animation.push("Frame 1", &Grid::<bool>::parse(&ctx, "#.\n.#").unwrap());
+    animation.push("Frame 2", &Grid::<bool>::parse(&ctx, "##\n.#\n..").unwrap());
+    animation
+}
+
+#[test]
+fn test_text_log() {
+    assert_eq!(
+        "Frame 1\n#.\n.#\n\nFrame 2\n##\n.#\n..\n\n",
+        test_animation().text_log()
+    );
+}
+
+#[test]
+fn test_gif() {
+    let gif = test_animation().gif(3, 10).unwrap();
+    assert_eq!(b"GIF89a", &gif[..6]);
+    // Logical screen size of the largest frame, little endian
+    assert_eq!([6, 0, 9, 0], gif[6..10]);
+    assert_eq!(Some(&0x3B), gif.last(), "GIF trailer");
+
+    let mut decoder = gif::DecodeOptions::new().read_info(&gif[..]).unwrap();
+    let mut frames = 0;
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        assert_eq!((6, 9, 10), (frame.width, frame.height, frame.delay));
+        frames += 1;
+    }
+    assert_eq!(2, frames);
+}
+
+#[test]
+#[should_panic(expected = "no color for 'x'")]
+fn test_missing_color() {
+    let mut animation = Animation::new(&[('.', [0, 0, 0])]);
+    animation.push("", &Grid::from_fn(1, 1, |_| 'x'));
+}
@@ -0,0 +1,231 @@
+//! Assign every item on the left side to a distinct candidate on the right side
+//!
+//! Used for the ticket fields of day 16 and the allergens of day 21.
+
+use crate::prelude::*;
+use std::fmt::{self, Debug, Display};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AssignmentError<L, R> {
+    /// No assignment exists, because `unassigned` cannot be given a distinct candidate
+    Infeasible { unassigned: L },
+    /// At least two different assignments exist
+    Ambiguous(Map<L, R>, Map<L, R>),
+}
+
+impl<L: Debug, R: Debug> Display for AssignmentError<L, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssignmentError::Infeasible { unassigned } => {
+                write!(f, "no distinct candidate left for {:?}", unassigned)
+            }
+            AssignmentError::Ambiguous(first, second) => write!(
+                f,
+                "multiple assignments possible, e.g., {:?} and {:?}",
+                first, second
+            ),
+        }
+    }
+}
+
+impl<L: Debug, R: Debug> std::error::Error for AssignmentError<L, R> {}
+
+/// Find the unique assignment of all left items to distinct right items, picked from their candidates
+///
+/// First, left items with a single candidate are fixed and their candidate is removed from all others, until no such item remains.
+/// Anything still open afterwards is solved with bipartite matching, which also tells apart ambiguous and infeasible problems.
+pub fn assign<L, R>(candidates: &Map<L, Set<R>>) -> Result<Map<L, R>, AssignmentError<L, R>>
+where
+    L: Clone + Ord,
+    R: Clone + Ord,
+{
+    let mut open: Map<&L, Set<&R>> = candidates
+        .iter()
+        .map(|(left, rights)| (left, rights.iter().collect()))
+        .collect();
+    let mut assignment: Map<&L, &R> = Map::new();
+
+    // Singleton propagation
+    loop {
+        if let Some((&left, _)) = open.iter().find(|(_, rights)| rights.is_empty()) {
+            return Err(AssignmentError::Infeasible {
+                unassigned: left.clone(),
+            });
+        }
+        let singletons = open
+            .iter()
+            .filter(|(_, rights)| rights.len() == 1)
+            .map(|(&left, rights)| (left, *rights.iter().next().unwrap()))
+            .collect_vec();
+        if singletons.is_empty() {
+            break;
+        }
+        for (left, right) in singletons {
+            // An earlier singleton of this batch may have taken the same candidate
+            if !open[left].contains(right) {
+                return Err(AssignmentError::Infeasible {
+                    unassigned: left.clone(),
+                });
+            }
+            open.remove(left);
+            assignment.insert(left, right);
+            open.values_mut().for_each(|rights| {
+                rights.remove(right);
+            });
+        }
+    }
+
+    let to_owned = |assignment: &Map<&L, &R>| -> Map<L, R> {
+        assignment
+            .iter()
+            .map(|(&left, &right)| (left.clone(), right.clone()))
+            .collect()
+    };
+    if open.is_empty() {
+        return Ok(to_owned(&assignment));
+    }
+
+    // Bipartite matching for the remaining items
+    let lefts = open.keys().copied().collect_vec();
+    let rights = open.values().flatten().copied().collect::<Set<_>>();
+    let rights = rights.into_iter().collect_vec();
+    let edges = lefts
+        .iter()
+        .map(|left| {
+            open[left]
+                .iter()
+                .map(|right| rights.binary_search(right).unwrap())
+                .collect_vec()
+        })
+        .collect_vec();
+
+    let mut matching = Matching {
+        edges: &edges,
+        left_match: vec![None; lefts.len()],
+        right_match: vec![None; rights.len()],
+    };
+    for (left, &item) in lefts.iter().enumerate() {
+        if !matching.augment(left, None) {
+            return Err(AssignmentError::Infeasible {
+                unassigned: item.clone(),
+            });
+        }
+    }
+    let extend = |matching: &Matching<'_>| {
+        let mut assignment = assignment.clone();
+        for (left, right) in matching.left_match.iter().enumerate() {
+            assignment.insert(lefts[left], rights[right.unwrap()]);
+        }
+        to_owned(&assignment)
+    };
+
+    // The matching is unique, if no left item can be matched differently
+    for left in 0..lefts.len() {
+        let mut other = matching.clone();
+        let right = other.left_match[left].take().unwrap();
+        other.right_match[right] = None;
+        if other.augment(left, Some(right)) {
+            return Err(AssignmentError::Ambiguous(
+                extend(&matching),
+                extend(&other),
+            ));
+        }
+    }
+    Ok(extend(&matching))
+}
+
+/// Maximum bipartite matching with augmenting paths
+#[derive(Clone)]
+struct Matching<'a> {
+    /// Right indices adjacent to each left index
+    edges: &'a [Vec<usize>],
+    left_match: Vec<Option<usize>>,
+    right_match: Vec<Option<usize>>,
+}
+
+impl Matching<'_> {
+    /// Try to match the unmatched `left`, without using the edge to `forbidden`
+    fn augment(&mut self, left: usize, forbidden: Option<usize>) -> bool {
+        let mut visited = vec![false; self.right_match.len()];
+        self.augment_rec(left, forbidden, &mut visited)
+    }
+
+    fn augment_rec(&mut self, left: usize, forbidden: Option<usize>, visited: &mut [bool]) -> bool {
+        for &right in &self.edges[left] {
+            if Some(right) == forbidden || visited[right] {
+                continue;
+            }
+            visited[right] = true;
+            let free = match self.right_match[right] {
+                None => true,
+                Some(other) => self.augment_rec(other, None, visited),
+            };
+            if free {
+                self.left_match[left] = Some(right);
+                self.right_match[right] = Some(left);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+fn candidates(input: &[(&'static str, &[u32])]) -> Map<&'static str, Set<u32>> {
+    input
+        .iter()
+        .map(|&(left, rights)| (left, rights.iter().copied().collect()))
+        .collect()
+}
+
+#[test]
+fn test_unique() {
+    let candidates = candidates(&[("a", &[1, 2, 3]), ("b", &[2]), ("c", &[2, 3])]);
+    let expected: Map<_, _> = vec![("a", 1), ("b", 2), ("c", 3)].into_iter().collect();
+    assert_eq!(Ok(expected), assign(&candidates));
+}
+
+#[test]
+fn test_ambiguous() {
+    let candidates = candidates(&[("a", &[1, 2]), ("b", &[2, 3]), ("c", &[1, 3]), ("d", &[4])]);
+    match assign(&candidates) {
+        Err(AssignmentError::Ambiguous(first, second)) => {
+            assert_ne!(first, second);
+            for assignment in &[first, second] {
+                assert_eq!(4, assignment.values().collect::<Set<_>>().len());
+                assert!(assignment
+                    .iter()
+                    .all(|(left, right)| candidates[left].contains(right)));
+            }
+        }
+        res => panic!("Expected an ambiguous assignment, got {:?}", res),
+    }
+}
+
+#[test]
+fn test_infeasible() {
+    // Found during propagation
+    let candidates1 = candidates(&[("a", &[1]), ("b", &[1, 2]), ("c", &[2])]);
+    assert!(matches!(
+        assign(&candidates1),
+        Err(AssignmentError::Infeasible { .. })
+    ));
+    // Two singletons with the same candidate
+    let candidates3 = candidates(&[("a", &[1]), ("b", &[1])]);
+    assert_eq!(
+        Err(AssignmentError::Infeasible { unassigned: "b" }),
+        assign(&candidates3)
+    );
+    // The singletons of one batch take all candidates of another item
+    let candidates4 = candidates(&[("a", &[1]), ("b", &[2]), ("c", &[1, 2])]);
+    assert_eq!(
+        Err(AssignmentError::Infeasible { unassigned: "c" }),
+        assign(&candidates4)
+    );
+    // Found by the matching
+    let candidates2 = candidates(&[("a", &[1, 2]), ("b", &[1, 2]), ("c", &[1, 2])]);
+    assert_eq!(
+        Err(AssignmentError::Infeasible { unassigned: "c" }),
+        assign(&candidates2)
+    );
+}
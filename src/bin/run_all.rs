@@ -0,0 +1,134 @@
+//! Run every registered `#[aoc]` solver and print a table of the answers and runtimes
+//!
+//! An optional day number as first argument only runs the solvers of that day.
+//! Days without an input file in `input/2020` are left out.
+
+use advent_of_code_2020::*;
+use aoc_runner::{ArcStr, Runner};
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+type SolverFactory = fn(ArcStr) -> Result<Box<dyn Runner>, Box<dyn Error>>;
+
+struct Solver {
+    day: u32,
+    part: u32,
+    name: Option<&'static str>,
+    input: &'static str,
+    factory: SolverFactory,
+}
+
+include!(concat!(env!("OUT_DIR"), "/solvers.rs"));
+
+struct Row {
+    day: String,
+    part: String,
+    answer: String,
+    generator: String,
+    runner: String,
+}
+
+fn run(solver: &Solver) -> Row {
+    let part = match solver.name {
+        Some(name) => format!("{} ({})", solver.part, name),
+        None => solver.part.to_string(),
+    };
+    let mut row = Row {
+        day: solver.day.to_string(),
+        part,
+        answer: String::new(),
+        generator: String::new(),
+        runner: String::new(),
+    };
+
+    let start_time = Instant::now();
+    let runner = match (solver.factory)(ArcStr::from(solver.input)) {
+        Ok(runner) => runner,
+        Err(err) => {
+            row.answer = format!("FAILED while generating: {}", err);
+            return row;
+        }
+    };
+    let inter_time = Instant::now();
+    row.generator = format_duration(inter_time - start_time);
+
+    match runner.try_run() {
+        Ok(answer) => {
+            row.runner = format_duration(inter_time.elapsed());
+            row.answer = answer.to_string();
+        }
+        Err(err) => row.answer = format!("FAILED while running: {}", err),
+    }
+    row
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.3?}", duration)
+}
+
+fn main() {
+    let day_filter: Option<u32> = std::env::args().nth(1).map(|day| {
+        day.trim_start_matches("day")
+            .parse()
+            .expect("The argument must be a day number")
+    });
+
+    let header = Row {
+        day: "Day".to_string(),
+        part: "Part".to_string(),
+        answer: "Answer".to_string(),
+        generator: "Generator".to_string(),
+        runner: "Runner".to_string(),
+    };
+    let mut rows = vec![header];
+    let total_time = Instant::now();
+    for solver in SOLVERS {
+        if let Some(day) = day_filter {
+            if day != solver.day {
+                continue;
+            }
+        }
+        rows.push(run(solver));
+    }
+    let total_time = total_time.elapsed();
+
+    let width = |f: fn(&Row) -> &str| rows.iter().map(|row| f(row).chars().count()).max().unwrap();
+    let day_width = width(|row| &row.day);
+    let part_width = width(|row| &row.part);
+    let answer_width = width(|row| &row.answer);
+    let generator_width = width(|row| &row.generator);
+    let runner_width = width(|row| &row.runner);
+
+    println!("Advent of code {}", YEAR);
+    for (i, row) in rows.iter().enumerate() {
+        println!(
+            "{:>dw$} | {:<pw$} | {:<aw$} | {:>gw$} | {:>rw$}",
+            row.day,
+            row.part,
+            row.answer,
+            row.generator,
+            row.runner,
+            dw = day_width,
+            pw = part_width,
+            aw = answer_width,
+            gw = generator_width,
+            rw = runner_width,
+        );
+        if i == 0 {
+            println!(
+                "{:-<dw$}-+-{:-<pw$}-+-{:-<aw$}-+-{:-<gw$}-+-{:-<rw$}",
+                "",
+                "",
+                "",
+                "",
+                "",
+                dw = day_width,
+                pw = part_width,
+                aw = answer_width,
+                gw = generator_width,
+                rw = runner_width,
+            );
+        }
+    }
+    println!("\nTotal: {}", format_duration(total_time));
+}
@@ -0,0 +1,23 @@
+//! Write the visualizations of a day into files
+//!
+//! Usage: `visualize <day> [output directory]`, the output directory defaults to `visualizations`.
+
+use advent_of_code_2020::visualize;
+use std::error::Error;
+use std::path::PathBuf;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = std::env::args().skip(1);
+    let day: u32 = args
+        .next()
+        .ok_or("The first argument must be a day number")?
+        .trim_start_matches("day")
+        .parse()?;
+    let dir = PathBuf::from(args.next().unwrap_or_else(|| "visualizations".to_string()));
+
+    let input = std::fs::read_to_string(format!("input/2020/day{}.txt", day))?;
+    for path in visualize(day, input.trim(), &dir)? {
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
@@ -45,9 +45,12 @@
 //!
 //! In your expense report, **what is the product of the three entries that sum to 2020**?
 
+use crate::prelude::*;
+
 #[aoc_generator(day1)]
-fn input_generator(input: &str) -> Vec<u32> {
-    input.split('\n').map(|x| x.parse().unwrap()).collect()
+fn input_generator(input: &str) -> Result<Vec<u32>, ParseError> {
+    let ctx = ParseContext::new(1, input);
+    input.split('\n').map(|x| ctx.parse(x)).collect()
 }
 
 #[aoc(day1, part1)]
@@ -86,13 +89,13 @@ fn test_part1() {
 299
 675
 1456"#;
-    let values = input_generator(input);
+    let values = input_generator(input).unwrap();
     assert_eq!(514579, part1(&values));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day1.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day1.txt").trim()).unwrap();
     assert_eq!(545379, part1(&values));
 }
 
@@ -104,12 +107,12 @@ fn test_part2() {
 299
 675
 1456"#;
-    let values = input_generator(input);
+    let values = input_generator(input).unwrap();
     assert_eq!(241861950, part2(&values));
 }
 
 #[test]
 fn test_part2_solution() {
-    let values = input_generator(include_str!("../input/2020/day1.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day1.txt").trim()).unwrap();
     assert_eq!(257778836, part2(&values));
 }
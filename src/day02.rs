@@ -73,8 +73,15 @@ impl PuzzleInput {
 }
 
 #[aoc_generator(day2)]
-fn input_generator(input: &str) -> Vec<PuzzleInput> {
-    input.split('\n').map(|x| x.parse().unwrap()).collect()
+fn input_generator(input: &str) -> Result<Vec<PuzzleInput>, ParseError> {
+    let ctx = ParseContext::new(2, input);
+    input
+        .split('\n')
+        .map(|x| {
+            x.parse()
+                .map_err(|_| ctx.error(x, "expected `<min>-<max> <char>: <password>`, got"))
+        })
+        .collect()
 }
 
 #[aoc(day2, part1)]
@@ -92,13 +99,13 @@ fn test_part1() {
     let input = r#"1-3 a: abcde
 1-3 b: cdefg
 2-9 c: ccccccccc"#;
-    let values = input_generator(input);
+    let values = input_generator(input).unwrap();
     assert_eq!(2, part1(&values));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day2.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day2.txt").trim()).unwrap();
     assert_eq!(591, part1(&values));
 }
 
@@ -107,12 +114,12 @@ fn test_part2() {
     let input = r#"1-3 a: abcde
 1-3 b: cdefg
 2-9 c: ccccccccc"#;
-    let values = input_generator(input);
+    let values = input_generator(input).unwrap();
     assert_eq!(1, part2(&values));
 }
 
 #[test]
 fn test_part2_solution() {
-    let values = input_generator(include_str!("../input/2020/day2.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day2.txt").trim()).unwrap();
     assert_eq!(335, part2(&values));
 }
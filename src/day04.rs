@@ -139,20 +139,21 @@
 //! Continue to treat `cid` as optional.
 //! **In your batch file, how many passports are valid?**
 
+use crate::prelude::*;
+
 #[aoc_generator(day4)]
-fn input_generator(input: &str) -> Vec<Vec<(String, String)>> {
+fn input_generator(input: &str) -> Result<Vec<Vec<(String, String)>>, ParseError> {
+    let ctx = ParseContext::new(4, input);
     input
         .split("\n\n")
         .map(|passport| {
             passport
-                .replace("\n", " ")
-                .split(' ')
-                .map(|fields| {
-                    let mut fields = fields.splitn(2, ':');
-                    (
-                        fields.next().unwrap().to_string(),
-                        fields.next().unwrap().to_string(),
-                    )
+                .split_whitespace()
+                .map(|field| {
+                    let mut parts = field.splitn(2, ':');
+                    let key = parts.next().unwrap_or_default();
+                    let value = ctx.require(parts.next(), field, "`:` between key and value")?;
+                    Ok((key.to_string(), value.to_string()))
                 })
                 .collect()
         })
@@ -256,31 +257,31 @@ fn all_parts_valied(fields: &[(String, String)]) -> bool {
 
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(2, part1(&values));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day4.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day4.txt").trim()).unwrap();
     assert_eq!(250, part1(&values));
 }
 
 #[test]
 fn test_part2_invalids() {
-    let values = input_generator(PUZZLE_INVALID_PASSPORTS);
+    let values = input_generator(PUZZLE_INVALID_PASSPORTS).unwrap();
     assert_eq!(0, part2(&values));
 }
 
 #[test]
 fn test_part2_valids() {
-    let values = input_generator(PUZZLE_VALID_PASSPORTS);
+    let values = input_generator(PUZZLE_VALID_PASSPORTS).unwrap();
     assert_eq!(4, part2(&values));
 }
 
 #[test]
 fn test_part2_solution() {
-    let values = input_generator(include_str!("../input/2020/day4.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day4.txt").trim()).unwrap();
     assert_eq!(158, part2(&values));
 }
 
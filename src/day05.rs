@@ -65,68 +65,272 @@
 //! **What is the ID of your seat?**
 
 use crate::prelude::*;
+use std::fmt::{self, Display};
+use std::str::FromStr;
 
-#[aoc_generator(day5)]
-fn input_generator(input: &str) -> Vec<(u32, u32)> {
-    input.split('\n').map(parse_seating).collect()
+/// Number of row and column characters of a boarding pass
+const ROW_BITS: usize = 7;
+const COLUMN_BITS: usize = 3;
+
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct BoardingPass {
+    row: u32,
+    column: u32,
 }
 
-fn parse_seating(s: &str) -> (u32, u32) {
-    let s = s
-        .replace("F", "0")
-        .replace("B", "1")
-        .replace("L", "0")
-        .replace("R", "1");
-    let row = u32::from_str_radix(&s[0..7], 2).unwrap();
-    let col = u32::from_str_radix(&s[7..10], 2).unwrap();
-    (row, col)
+impl BoardingPass {
+    fn seat_id(&self) -> u32 {
+        self.row * 8 + self.column
+    }
+
+    #[cfg(test)]
+    fn from_seat_id(seat_id: u32) -> Self {
+        assert!(
+            seat_id < 1 << (ROW_BITS + COLUMN_BITS),
+            "Seat ID out of range"
+        );
+        BoardingPass {
+            row: seat_id / 8,
+            column: seat_id % 8,
+        }
+    }
 }
 
-#[aoc(day5, part1)]
-fn part1(input: &[(u32, u32)]) -> u32 {
-    input
-        .iter()
-        .map(|(row, col)| *row * 8 + *col)
-        .max()
-        .unwrap()
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum BoardingPassError {
+    /// The boarding pass has this many chars instead of 10
+    Length(usize),
+    /// A char which is not allowed at this byte offset
+    InvalidChar { offset: usize, found: char },
 }
 
-#[aoc(day5, part2)]
-fn part2(input: &[(u32, u32)]) -> u32 {
-    let set: Set<(_, _)> = input.iter().copied().collect();
-    for row in 12..123 {
-        for col in 0..8 {
-            if set.get(&(row, col)).is_none() {
-                // println!("{} - {}" ,row, col)
-                return row * 8 + col;
+impl Display for BoardingPassError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardingPassError::Length(len) => write!(
+                f,
+                "boarding pass must have {} characters, found {}",
+                ROW_BITS + COLUMN_BITS,
+                len
+            ),
+            BoardingPassError::InvalidChar { offset, found } => write!(
+                f,
+                "invalid seat character {:?}, expected {}",
+                found,
+                expected_chars(*offset)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BoardingPassError {}
+
+/// The chars allowed at the byte `offset` of a boarding pass
+fn expected_chars(offset: usize) -> &'static str {
+    if offset < ROW_BITS {
+        "F or B"
+    } else {
+        "L or R"
+    }
+}
+
+impl FromStr for BoardingPass {
+    type Err = BoardingPassError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let len = s.chars().count();
+        if len != ROW_BITS + COLUMN_BITS {
+            return Err(BoardingPassError::Length(len));
+        }
+        let mut pass = BoardingPass { row: 0, column: 0 };
+        for (offset, c) in s.char_indices() {
+            // `offset` equals the char index, as all valid chars are ASCII
+            let (bits, lower, upper) = if offset < ROW_BITS {
+                (&mut pass.row, 'F', 'B')
+            } else {
+                (&mut pass.column, 'L', 'R')
+            };
+            *bits <<= 1;
+            if c == upper {
+                *bits |= 1;
+            } else if c != lower {
+                return Err(BoardingPassError::InvalidChar { offset, found: c });
             }
         }
+        Ok(pass)
+    }
+}
+
+impl Display for BoardingPass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for bit in (0..ROW_BITS).rev() {
+            let c = if self.row >> bit & 1 == 1 { 'B' } else { 'F' };
+            write!(f, "{}", c)?;
+        }
+        for bit in (0..COLUMN_BITS).rev() {
+            let c = if self.column >> bit & 1 == 1 {
+                'R'
+            } else {
+                'L'
+            };
+            write!(f, "{}", c)?;
+        }
+        Ok(())
     }
-    0
+}
+
+#[aoc_generator(day5)]
+fn input_generator(input: &str) -> Result<Vec<BoardingPass>, ParseError> {
+    let ctx = ParseContext::new(5, input);
+    input
+        .split('\n')
+        .map(|line| {
+            line.parse().map_err(|err| match err {
+                BoardingPassError::Length(_) => ctx.error(line, err),
+                // The error points at the invalid char, so the reason does not repeat it
+                BoardingPassError::InvalidChar { offset, found } => ctx.error(
+                    &line[offset..offset + found.len_utf8()],
+                    format!(
+                        "expected seat character {} instead of",
+                        expected_chars(offset)
+                    ),
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Draw the occupied (`#`) and free (`.`) seats of all rows between the first and the last boarding pass
+///
+/// Each line shows the row number followed by the columns, with the aisle between columns 3 and 4.
+fn seat_map(passes: &[BoardingPass]) -> String {
+    let occupied: Set<_> = passes.iter().copied().collect();
+    let (first, last) = match passes.iter().map(|pass| pass.row).minmax().into_option() {
+        Some(rows) => rows,
+        None => return String::new(),
+    };
+    let mut map = String::new();
+    for row in first..=last {
+        let seat = |column| {
+            if occupied.contains(&BoardingPass { row, column }) {
+                '#'
+            } else {
+                '.'
+            }
+        };
+        let left: String = (0..4).map(seat).collect();
+        let right: String = (4..8).map(seat).collect();
+        map += &format!("{:>3} {} {}\n", row, left, right);
+    }
+    map
+}
+
+#[aoc(day5, part1)]
+fn part1(input: &[BoardingPass]) -> u32 {
+    input.iter().map(BoardingPass::seat_id).max().unwrap()
+}
+
+/// Find the only free seat between the lowest and the highest seat ID
+#[aoc(day5, part2)]
+fn part2(input: &[BoardingPass]) -> u32 {
+    let seat_ids = input
+        .iter()
+        .map(BoardingPass::seat_id)
+        .sorted()
+        .collect_vec();
+    seat_ids
+        .iter()
+        .tuple_windows()
+        .find(|&(&a, &b)| b - a == 2)
+        .map(|(&a, _)| a + 1)
+        .unwrap_or_else(|| panic!("No free seat in the plane:\n{}", seat_map(input)))
 }
 
 #[test]
 fn test_parse_seating() {
-    assert_eq!((70, 7), parse_seating("BFFFBBFRRR"));
-    assert_eq!((14, 7), parse_seating("FFFBBBFRRR"));
-    assert_eq!((102, 4), parse_seating("BBFFBBFRLL"));
+    let pass = |s: &str| {
+        s.parse::<BoardingPass>()
+            .map(|pass| (pass.row, pass.column))
+    };
+    assert_eq!(Ok((44, 5)), pass("FBFBBFFRLR"));
+    assert_eq!(Ok((70, 7)), pass("BFFFBBFRRR"));
+    assert_eq!(Ok((14, 7)), pass("FFFBBBFRRR"));
+    assert_eq!(Ok((102, 4)), pass("BBFFBBFRLL"));
+}
+
+#[test]
+fn test_round_trip() {
+    for seat_id in 0..1024 {
+        let pass = BoardingPass::from_seat_id(seat_id);
+        assert_eq!(seat_id, pass.seat_id());
+        assert_eq!(Ok(pass), pass.to_string().parse());
+    }
+    assert_eq!("FBFBBFFRLR", BoardingPass::from_seat_id(357).to_string());
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        Err(BoardingPassError::Length(9)),
+        "FBFBBFFRL".parse::<BoardingPass>()
+    );
+    assert_eq!(
+        Err(BoardingPassError::InvalidChar {
+            offset: 6,
+            found: 'L'
+        }),
+        "FBFBBFLRLR".parse::<BoardingPass>()
+    );
+    let err = input_generator("FBFBBFFRLR\nFBFBBFFRBR").unwrap_err();
+    assert_eq!(
+        "day5 line 2 column 9: expected seat character L or R instead of 'B'",
+        err.to_string()
+    );
+    assert_eq!(
+        "invalid seat character 'B', expected L or R",
+        "FBFBBFFRBR"
+            .parse::<BoardingPass>()
+            .unwrap_err()
+            .to_string()
+    );
+    let err = input_generator("FBFBBFFRLR\nFBFBBFFRLRR").unwrap_err();
+    assert_eq!((2, 1), (err.line, err.column));
+    // Multi-byte chars are reported with their char position
+    let err = input_generator("FBFBBFFRLR\nFBFäBFFRLR").unwrap_err();
+    assert_eq!((2, 4), (err.line, err.column));
+}
+
+#[test]
+fn test_seat_map() {
+    let passes = input_generator("FBFBBFFRLR\nFBFBBFBLLL\nFBFBBFBLLR").unwrap();
+    assert_eq!(" 44 .... .#..\n 45 ##.. ....\n", seat_map(&passes));
+}
+
+#[test]
+fn test_part2() {
+    // Seats 5 to 9 with 7 missing, at a different range than the real input
+    let passes = [5, 6, 8, 9]
+        .iter()
+        .map(|&id| BoardingPass::from_seat_id(id))
+        .collect_vec();
+    assert_eq!(7, part2(&passes));
 }
 
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(820, part1(&values));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day5.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day5.txt").trim()).unwrap();
     assert_eq!(989, part1(&values));
 }
 
 #[test]
 fn test_part2_solution() {
-    let values = input_generator(include_str!("../input/2020/day5.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day5.txt").trim()).unwrap();
     assert_eq!(548, part2(&values));
 }
 
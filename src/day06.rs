@@ -96,13 +96,26 @@
 use crate::prelude::*;
 
 #[aoc_generator(day6)]
-fn input_generator(input: &str) -> Vec<Vec<Set<char>>> {
+fn input_generator(input: &str) -> Result<Vec<Vec<Set<char>>>, ParseError> {
+    let ctx = ParseContext::new(6, input);
     input
         .split("\n\n")
         .map(|group_answers| {
             group_answers
                 .split('\n')
-                .map(|answers| answers.chars().collect())
+                .map(|answers| {
+                    answers
+                        .char_indices()
+                        .map(|(idx, c)| {
+                            if c.is_ascii_lowercase() {
+                                Ok(c)
+                            } else {
+                                Err(ctx
+                                    .error(&answers[idx..idx + c.len_utf8()], "unknown question"))
+                            }
+                        })
+                        .collect()
+                })
                 .collect()
         })
         .collect()
@@ -142,25 +155,25 @@ fn part2(input: &[Vec<Set<char>>]) -> usize {
 
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(11, part1(&values));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day6.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day6.txt").trim()).unwrap();
     assert_eq!(6259, part1(&values));
 }
 
 #[test]
 fn test_part2() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(6, part2(&values));
 }
 
 #[test]
 fn test_part2_solution() {
-    let values = input_generator(include_str!("../input/2020/day6.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day6.txt").trim()).unwrap();
     assert_eq!(3178, part2(&values));
 }
 
@@ -72,23 +72,29 @@
 //!
 //! **How many individual bags are required inside your single shiny gold bag?**
 
+use crate::dot::Dot;
 use crate::prelude::*;
 
 #[aoc_generator(day7)]
-fn input_generator(input: &str) -> Map<String, Vec<(usize, String)>> {
+fn input_generator(input: &str) -> Result<Map<String, Vec<(usize, String)>>, ParseError> {
+    let ctx = ParseContext::new(7, input);
     let re_bags = Regex::new(r"(?P<count>\d+) (?P<color>\w+ \w+) bag").unwrap();
 
     let mut res: Map<String, Vec<(usize, String)>> = Map::new();
-    input.split('\n').for_each(|line| {
-        let outer = line.split(" bag").next().unwrap();
+    for line in input.split('\n') {
+        let outer = ctx.require(
+            line.find(" bags contain ").map(|idx| &line[..idx]),
+            line,
+            "`bags contain`",
+        )?;
         let entry = res.entry(outer.to_string()).or_default();
         for capture in re_bags.captures_iter(line) {
-            let count = capture["count"].parse().unwrap();
+            let count = ctx.parse(capture.name("count").unwrap().as_str())?;
             let inner = capture["color"].to_string();
             entry.push((count, inner))
         }
-    });
-    res
+    }
+    Ok(res)
 }
 
 #[aoc(day7, part1)]
@@ -152,42 +158,498 @@ fn part2_memoization(input: &Map<String, Vec<(usize, String)>>) -> usize {
     get_color_contains_bags("shiny gold", input, &mut cache)
 }
 
+/// Index of an interned bag color in the [`BagGraph`]
+type ColorId = usize;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum BagError {
+    UnknownColor(String),
+    /// The bags contain each other, starting and ending with the same color
+    Cycle(Vec<String>),
+}
+
+impl std::fmt::Display for BagError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BagError::UnknownColor(color) => write!(f, "unknown bag color `{}`", color),
+            BagError::Cycle(colors) => write!(
+                f,
+                "bags contain themselves, thus infinitely many bags: {}",
+                colors.join(" -> ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BagError {}
+
+/// The bag rules with interned colors, such that queries only work on indices
+struct BagGraph {
+    names: Vec<String>,
+    ids: HashMap<String, ColorId>,
+    /// Inner bags and their count for every bag
+    contents: Vec<Vec<(usize, ColorId)>>,
+    /// All bags directly containing a bag
+    containers: Vec<Vec<ColorId>>,
+}
+
+/// State of a bag during the depth-first search in [`BagGraph::count_contained`]
+#[derive(Copy, Clone)]
+enum Visit {
+    New,
+    InProgress,
+    Done(usize),
+}
+
+impl BagGraph {
+    fn new(rules: &Map<String, Vec<(usize, String)>>) -> Self {
+        let mut graph = BagGraph {
+            names: Vec::new(),
+            ids: HashMap::new(),
+            contents: Vec::new(),
+            containers: Vec::new(),
+        };
+        for (outer, inners) in rules {
+            let outer = graph.intern(outer);
+            for (count, inner) in inners {
+                let inner = graph.intern(inner);
+                graph.contents[outer].push((*count, inner));
+                graph.containers[inner].push(outer);
+            }
+        }
+        graph
+    }
+
+    fn intern(&mut self, color: &str) -> ColorId {
+        if let Some(&id) = self.ids.get(color) {
+            return id;
+        }
+        let id = self.names.len();
+        self.names.push(color.to_string());
+        self.ids.insert(color.to_string(), id);
+        self.contents.push(Vec::new());
+        self.containers.push(Vec::new());
+        id
+    }
+
+    fn id(&self, color: &str) -> Result<ColorId, BagError> {
+        self.ids
+            .get(color)
+            .copied()
+            .ok_or_else(|| BagError::UnknownColor(color.to_string()))
+    }
+
+    /// All bags which eventually contain a bag of `color`
+    fn containers_of(&self, color: &str) -> Result<Set<&str>, BagError> {
+        let mut seen = vec![false; self.names.len()];
+        let mut unexplored = vec![self.id(color)?];
+        let mut res = Set::new();
+        while let Some(id) = unexplored.pop() {
+            for &outer in &self.containers[id] {
+                if !seen[outer] {
+                    seen[outer] = true;
+                    res.insert(self.names[outer].as_str());
+                    unexplored.push(outer);
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    /// Number of bags inside a bag of `color`
+    fn count_contained(&self, color: &str) -> Result<usize, BagError> {
+        let mut visits = vec![Visit::New; self.names.len()];
+        let mut stack = Vec::new();
+        self.count_contained_rec(self.id(color)?, &mut visits, &mut stack)
+    }
+
+    fn count_contained_rec(
+        &self,
+        id: ColorId,
+        visits: &mut [Visit],
+        stack: &mut Vec<ColorId>,
+    ) -> Result<usize, BagError> {
+        match visits[id] {
+            Visit::Done(count) => return Ok(count),
+            Visit::InProgress => {
+                let start = stack.iter().position(|&other| other == id).unwrap();
+                let cycle = stack[start..].iter().chain(Some(&id));
+                return Err(BagError::Cycle(
+                    cycle.map(|&id| self.names[id].clone()).collect(),
+                ));
+            }
+            Visit::New => {}
+        }
+
+        visits[id] = Visit::InProgress;
+        stack.push(id);
+        let mut count = 0;
+        for &(inner_count, inner) in &self.contents[id] {
+            count += (self.count_contained_rec(inner, visits, stack)? + 1) * inner_count;
+        }
+        stack.pop();
+        visits[id] = Visit::Done(count);
+        Ok(count)
+    }
+
+    /// Shortest chain of bags from `outer` to `inner`, where each bag directly contains the next one
+    ///
+    /// None of the solvers needs the chain, so it is only compiled for the tests.
+    #[cfg(test)]
+    fn path(&self, outer: &str, inner: &str) -> Result<Option<Vec<&str>>, BagError> {
+        let (outer, inner) = (self.id(outer)?, self.id(inner)?);
+        let mut predecessor: Vec<Option<ColorId>> = vec![None; self.names.len()];
+        let mut queue = std::collections::VecDeque::new();
+        queue.push_back(outer);
+        while let Some(id) = queue.pop_front() {
+            if id == inner {
+                let mut path = vec![self.names[id].as_str()];
+                let mut id = id;
+                while let Some(pred) = predecessor[id].filter(|_| id != outer) {
+                    path.push(&self.names[pred]);
+                    id = pred;
+                }
+                path.reverse();
+                return Ok(Some(path));
+            }
+            for &(_, next) in &self.contents[id] {
+                if next != outer && predecessor[next].is_none() {
+                    predecessor[next] = Some(id);
+                    queue.push_back(next);
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Fail with the first found bags which contain each other
+    fn check_acyclic(&self) -> Result<(), BagError> {
+        let mut visits = vec![Visit::New; self.names.len()];
+        for id in 0..self.names.len() {
+            self.count_contained_rec(id, &mut visits, &mut Vec::new())?;
+        }
+        Ok(())
+    }
+
+    /// Graphviz graph of all bag rules, where the edges are labeled with the number of inner bags
+    ///
+    /// The bag `highlight` and all bags inside of it are highlighted.
+    fn to_dot(&self, highlight: Option<&str>) -> Result<String, BagError> {
+        let mut highlighted = vec![false; self.names.len()];
+        if let Some(color) = highlight {
+            let mut unexplored = vec![self.id(color)?];
+            while let Some(id) = unexplored.pop() {
+                if !highlighted[id] {
+                    highlighted[id] = true;
+                    unexplored.extend(self.contents[id].iter().map(|&(_, inner)| inner));
+                }
+            }
+        }
+
+        let by_name = |&id: &ColorId| &self.names[id];
+        let mut dot = Dot::new("bags");
+        for id in (0..self.names.len()).sorted_by_key(by_name) {
+            dot.node(&self.names[id], &self.names[id], highlighted[id]);
+        }
+        for id in (0..self.names.len()).sorted_by_key(by_name) {
+            for &(count, inner) in self.contents[id]
+                .iter()
+                .sorted_by_key(|(_, inner)| by_name(inner))
+            {
+                dot.edge(
+                    &self.names[id],
+                    &self.names[inner],
+                    &count.to_string(),
+                    highlighted[id],
+                );
+            }
+        }
+        Ok(dot.finish())
+    }
+}
+
+/// Build the [`BagGraph`], rejecting rules where bags contain themselves
+#[aoc_generator(day7, part1, bag_graph)]
+#[aoc_generator(day7, part2, bag_graph)]
+fn input_generator_bag_graph(input: &str) -> Result<BagGraph, ParseError> {
+    let graph = BagGraph::new(&input_generator(input)?);
+    if let Err(err) = graph.check_acyclic() {
+        // Point at the rule of the first bag in the cycle
+        let color = match &err {
+            BagError::Cycle(colors) => &colors[0],
+            BagError::UnknownColor(_) => unreachable!("All colors are interned"),
+        };
+        let prefix = format!("{} bags contain ", color);
+        let rule = input
+            .split('\n')
+            .find(|line| line.starts_with(&prefix))
+            .unwrap();
+        return Err(ParseContext::new(7, input).error(&rule[..color.len()], err));
+    }
+    Ok(graph)
+}
+
+/// Draw the bag rules as Graphviz graph, highlighting the `shiny gold` bag and its contents
+pub fn graphs(input: &str) -> Result<Vec<(&'static str, String)>, ParseError> {
+    let graph = input_generator_bag_graph(input)?;
+    let highlight = Some("shiny gold").filter(|color| graph.ids.contains_key(*color));
+    let dot = graph
+        .to_dot(highlight)
+        .expect("The highlighted color is known");
+    Ok(vec![("bags", dot)])
+}
+
+#[aoc(day7, part1, bag_graph)]
+fn part1_bag_graph(graph: &BagGraph) -> Result<usize, BagError> {
+    Ok(graph.containers_of("shiny gold")?.len())
+}
+
+#[aoc(day7, part2, bag_graph)]
+fn part2_bag_graph(graph: &BagGraph) -> Result<usize, BagError> {
+    graph.count_contained("shiny gold")
+}
+
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(4, part1(&values));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day7.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day7.txt").trim()).unwrap();
     assert_eq!(300, part1(&values));
 }
 
 #[test]
 fn test_part2() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(32, part2_memoization(&values));
 }
 
 #[test]
 fn test_part2_example2() {
-    let values = input_generator(PUZZLE2);
+    let values = input_generator(PUZZLE2).unwrap();
     assert_eq!(126, part2_memoization(&values));
 }
 
 #[test]
 fn test_part2_solution_naive() {
-    let values = input_generator(include_str!("../input/2020/day7.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day7.txt").trim()).unwrap();
     assert_eq!(8030, part2_naive(&values));
 }
 
 #[test]
 fn test_part2_solution_memoization() {
-    let values = input_generator(include_str!("../input/2020/day7.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day7.txt").trim()).unwrap();
     assert_eq!(8030, part2_memoization(&values));
 }
 
+#[test]
+fn test_bag_graph() {
+    let graph = input_generator_bag_graph(PUZZLE).unwrap();
+    assert_eq!(Ok(4), part1_bag_graph(&graph));
+    assert_eq!(Ok(32), part2_bag_graph(&graph));
+    let graph = input_generator_bag_graph(PUZZLE2).unwrap();
+    assert_eq!(Ok(126), part2_bag_graph(&graph));
+
+    let graph = input_generator_bag_graph(include_str!("../input/2020/day7.txt").trim()).unwrap();
+    assert_eq!(Ok(300), part1_bag_graph(&graph));
+    assert_eq!(Ok(8030), part2_bag_graph(&graph));
+}
+
+#[test]
+fn test_path() {
+    let graph = input_generator_bag_graph(PUZZLE2).unwrap();
+    assert_eq!(
+        Ok(Some(vec![
+            "shiny gold",
+            "dark red",
+            "dark orange",
+            "dark yellow",
+            "dark green",
+            "dark blue",
+            "dark violet"
+        ])),
+        graph.path("shiny gold", "dark violet")
+    );
+    assert_eq!(
+        Ok(Some(vec!["dark green", "dark blue"])),
+        graph.path("dark green", "dark blue")
+    );
+    assert_eq!(Ok(None), graph.path("dark violet", "shiny gold"));
+
+    // The deepest bag inside shiny gold is four steps away, each bag must directly contain the next one
+    let graph = input_generator_bag_graph(include_str!("../input/2020/day7.txt").trim()).unwrap();
+    let path = graph.path("shiny gold", "muted aqua").unwrap().unwrap();
+    assert_eq!(5, path.len());
+    assert_eq!(("shiny gold", "muted aqua"), (path[0], path[4]));
+    for (outer, inner) in path.iter().tuple_windows() {
+        let (outer, inner) = (graph.id(outer).unwrap(), graph.id(inner).unwrap());
+        assert!(graph.contents[outer].iter().any(|&(_, id)| id == inner));
+    }
+}
+
+#[test]
+fn test_bag_graph_queries() {
+    let graph = BagGraph::new(&input_generator(PUZZLE).unwrap());
+    assert_eq!(
+        Ok(
+            vec!["bright white", "light red", "muted yellow", "dark orange"]
+                .into_iter()
+                .collect()
+        ),
+        graph.containers_of("shiny gold")
+    );
+    assert_eq!(Ok(11), graph.count_contained("vibrant plum"));
+    assert_eq!(
+        Ok(Some(vec![
+            "light red",
+            "bright white",
+            "shiny gold",
+            "dark olive",
+            "dotted black"
+        ])),
+        graph.path("light red", "dotted black")
+    );
+    assert_eq!(
+        Ok(Some(vec!["faded blue"])),
+        graph.path("faded blue", "faded blue")
+    );
+    assert_eq!(Ok(None), graph.path("shiny gold", "light red"));
+    assert_eq!(
+        Err(BagError::UnknownColor("plaid pink".to_string())),
+        graph.path("plaid pink", "shiny gold")
+    );
+    assert_eq!(Ok(()), graph.check_acyclic());
+}
+
+#[test]
+fn test_bag_graph_cycle() {
+    let values = input_generator(
+        "light red bags contain 1 dark red bag.\n\
+         dark red bags contain 2 dark blue bags.\n\
+         dark blue bags contain 1 dark red bag, 1 faded blue bag.\n\
+         faded blue bags contain no other bags.",
+    )
+    .unwrap();
+    let graph = BagGraph::new(&values);
+    assert_eq!(Ok(0), graph.count_contained("faded blue"));
+    let err = graph.count_contained("light red").unwrap_err();
+    assert_eq!(
+        BagError::Cycle(vec![
+            "dark red".to_string(),
+            "dark blue".to_string(),
+            "dark red".to_string()
+        ]),
+        err
+    );
+    assert_eq!(
+        "bags contain themselves, thus infinitely many bags: dark red -> dark blue -> dark red",
+        err.to_string()
+    );
+    assert_eq!(
+        Err(BagError::Cycle(vec![
+            "dark blue".to_string(),
+            "dark red".to_string(),
+            "dark blue".to_string()
+        ])),
+        graph.check_acyclic()
+    );
+    assert_eq!(
+        Ok(Some(vec!["dark blue", "dark red"])),
+        graph.path("dark blue", "dark red")
+    );
+}
+
+#[test]
+fn test_bag_graph_generator_cycle() {
+    let err = input_generator_bag_graph(
+        "light red bags contain 1 dark red bag.\n\
+         dark red bags contain 2 dark blue bags.\n\
+         dark blue bags contain 1 dark red bag, 1 faded blue bag.\n\
+         faded blue bags contain no other bags.",
+    )
+    .map(|_| ())
+    .unwrap_err();
+    assert_eq!(
+        "day7 line 3 column 1: bags contain themselves, thus infinitely many bags: dark blue -> dark red -> dark blue 'dark blue'",
+        err.to_string()
+    );
+}
+
+#[test]
+fn test_to_dot() {
+    let graph = input_generator_bag_graph(PUZZLE2).unwrap();
+    assert_eq!(
+        Err(BagError::UnknownColor("plaid pink".to_string())),
+        graph.to_dot(Some("plaid pink"))
+    );
+    assert_eq!(
+        r#"digraph "bags" {
+    "dark blue" [label="dark blue", color=red, fontcolor=red];
+    "dark green" [label="dark green", color=red, fontcolor=red];
+    "dark orange" [label="dark orange"];
+    "dark red" [label="dark red"];
+    "dark violet" [label="dark violet", color=red, fontcolor=red];
+    "dark yellow" [label="dark yellow"];
+    "shiny gold" [label="shiny gold"];
+    "dark blue" -> "dark violet" [label="2", color=red, fontcolor=red];
+    "dark green" -> "dark blue" [label="2", color=red, fontcolor=red];
+    "dark orange" -> "dark yellow" [label="2"];
+    "dark red" -> "dark orange" [label="2"];
+    "dark yellow" -> "dark green" [label="2"];
+    "shiny gold" -> "dark red" [label="2"];
+}
+"#,
+        graph.to_dot(Some("dark green")).unwrap()
+    );
+
+    let graph = input_generator_bag_graph(PUZZLE).unwrap();
+    assert_eq!(
+        r#"digraph "bags" {
+    "bright white" [label="bright white"];
+    "dark olive" [label="dark olive"];
+    "dark orange" [label="dark orange"];
+    "dotted black" [label="dotted black"];
+    "faded blue" [label="faded blue"];
+    "light red" [label="light red"];
+    "muted yellow" [label="muted yellow"];
+    "shiny gold" [label="shiny gold"];
+    "vibrant plum" [label="vibrant plum"];
+    "bright white" -> "shiny gold" [label="1"];
+    "dark olive" -> "dotted black" [label="4"];
+    "dark olive" -> "faded blue" [label="3"];
+    "dark orange" -> "bright white" [label="3"];
+    "dark orange" -> "muted yellow" [label="4"];
+    "light red" -> "bright white" [label="1"];
+    "light red" -> "muted yellow" [label="2"];
+    "muted yellow" -> "faded blue" [label="9"];
+    "muted yellow" -> "shiny gold" [label="2"];
+    "shiny gold" -> "dark olive" [label="1"];
+    "shiny gold" -> "vibrant plum" [label="2"];
+    "vibrant plum" -> "dotted black" [label="6"];
+    "vibrant plum" -> "faded blue" [label="5"];
+}
+"#,
+        graph.to_dot(None).unwrap()
+    );
+}
+
+#[test]
+fn test_graphs() {
+    let graph = input_generator_bag_graph(PUZZLE).unwrap();
+    assert_eq!(
+        vec![("bags", graph.to_dot(Some("shiny gold")).unwrap())],
+        graphs(PUZZLE).unwrap()
+    );
+    let graph = input_generator_bag_graph("faded blue bags contain no other bags.").unwrap();
+    assert_eq!(
+        vec![("bags", graph.to_dot(None).unwrap())],
+        graphs("faded blue bags contain no other bags.").unwrap()
+    );
+}
+
 #[cfg(test)]
 static PUZZLE: &str = r#"light red bags contain 1 bright white bag, 2 muted yellow bags.
 dark orange bags contain 3 bright white bags, 4 muted yellow bags.
@@ -15,12 +15,12 @@
 //! Each instruction consists of an **operation** (`acc`, `jmp`, or `nop`) and an **argument** (a signed number like `+4` or `-20`).
 //!
 //! - `acc` increases or decreases a single global value called the **accumulator** by the value given in the argument.For example, `acc +7` would increase the accumulator by 7.
-//!     The accumulator starts at `0`.
-//!     After an `acc` instruction, the instruction immediately below it is executed next.
+//!   The accumulator starts at `0`.
+//!   After an `acc` instruction, the instruction immediately below it is executed next.
 //! - `jmp` **jumps** to a new instruction relative to itself.
-//!     The next instruction to execute is found using the argument as an **offset** from the `jmp` instruction; for example, `jmp +2` would skip the next instruction, `jmp +1` would continue to the instruction immediately below it, and `jmp -20` would cause the instruction 20 lines above to be executed next.
+//!   The next instruction to execute is found using the argument as an **offset** from the `jmp` instruction; for example, `jmp +2` would skip the next instruction, `jmp +1` would continue to the instruction immediately below it, and `jmp -20` would cause the instruction 20 lines above to be executed next.
 //! - `nop` stands for **No OPeration** - it does nothing.
-//!     The instruction immediately below it is executed next.
+//!   The instruction immediately below it is executed next.
 //!
 //! For example, consider the following program:
 //!
@@ -113,123 +113,672 @@
 
 use crate::prelude::*;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 struct Instruction {
     kind: InstructionKind,
     value: i32,
 }
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 enum InstructionKind {
     Acc,
     Nop,
     Jmp,
 }
 
+impl Instruction {
+    /// The instruction with nop and jmp swapped, or `None` for acc
+    fn flipped(self) -> Option<Self> {
+        let kind = match self.kind {
+            InstructionKind::Acc => return None,
+            InstructionKind::Nop => InstructionKind::Jmp,
+            InstructionKind::Jmp => InstructionKind::Nop,
+        };
+        Some(Instruction { kind, ..self })
+    }
+
+    /// Instruction pointer after executing this instruction at `ip`
+    fn next_ip(self, ip: isize) -> isize {
+        match self.kind {
+            InstructionKind::Jmp => ip + self.value as isize,
+            InstructionKind::Acc | InstructionKind::Nop => ip + 1,
+        }
+    }
+}
+
+impl std::fmt::Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self.kind {
+            InstructionKind::Acc => "acc",
+            InstructionKind::Nop => "nop",
+            InstructionKind::Jmp => "jmp",
+        };
+        write!(f, "{} {:+}", op, self.value)
+    }
+}
+
+/// Parse a program, as written by [`Listing`]
+///
+/// Besides the plain puzzle input, this understands `name:` labels in front of instructions, label names as arguments, and `;` comments.
 #[aoc_generator(day8)]
-fn input_generator(input: &str) -> Vec<Instruction> {
-    input
-        .split('\n')
-        .map(|line| {
+fn input_generator(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    let ctx = ParseContext::new(8, input);
+    let lines = input
+        .lines()
+        .map(|line| line.split(';').next().unwrap_or_default().trim())
+        .collect_vec();
+
+    // Split off all labels first, such that jumps can refer to later labels
+    let mut labels = Map::new();
+    let mut statements = Vec::with_capacity(lines.len());
+    for (ip, &line) in lines.iter().enumerate() {
+        let statement = match line.split_once(':') {
+            Some((label, statement)) => {
+                if labels.insert(label.trim(), ip as i32).is_some() {
+                    return Err(ctx.error(label, "duplicate label"));
+                }
+                statement.trim_start()
+            }
+            None => line,
+        };
+        statements.push(statement);
+    }
+
+    statements
+        .into_iter()
+        .enumerate()
+        .map(|(ip, line)| {
             let mut iter = line.split(' ');
-            let kind = match iter.next().unwrap() {
+            let op = iter.next().unwrap_or_default();
+            let kind = match op {
                 "acc" => InstructionKind::Acc,
                 "nop" => InstructionKind::Nop,
                 "jmp" => InstructionKind::Jmp,
-                _ => unreachable!("No other instructions exist"),
+                _ => return Err(ctx.error(op, "unknown operation")),
+            };
+            let arg = ctx.require(iter.next(), line, "argument")?;
+            let value = match labels.get(arg) {
+                Some(target) => target - ip as i32,
+                None => ctx.parse(arg)?,
             };
-            let value = iter.next().unwrap().parse().unwrap();
-            Instruction { kind, value }
+            Ok(Instruction { kind, value })
         })
         .collect()
 }
 
+/// How a program is printed by [`Listing`]
+#[derive(Copy, Clone, Debug, Default)]
+struct ListingOptions {
+    /// Replace the arguments of jmp instructions with a label at the target
+    labels: bool,
+    /// Add a comment to every instruction, if it is reachable and if it is part of the infinite loop
+    annotate: bool,
+}
+
+/// Printable program, which can be parsed again by [`input_generator`]
+struct Listing<'a> {
+    program: &'a [Instruction],
+    options: ListingOptions,
+}
+
+impl<'a> Listing<'a> {
+    fn new(program: &'a [Instruction], options: ListingOptions) -> Self {
+        Listing { program, options }
+    }
+
+    /// Address of the instruction a jmp jumps to, if it is inside the program
+    fn target(&self, ip: usize) -> Option<usize> {
+        let instruction = self.program[ip];
+        if instruction.kind != InstructionKind::Jmp {
+            return None;
+        }
+        let target = ip as isize + instruction.value as isize;
+        if 0 <= target && target < self.program.len() as isize {
+            Some(target as usize)
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for Listing<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = |ip: usize| format!("L{}", ip);
+        let targets: Set<usize> = if self.options.labels {
+            (0..self.program.len())
+                .filter_map(|ip| self.target(ip))
+                .collect()
+        } else {
+            Set::new()
+        };
+        let label_width = targets
+            .iter()
+            .map(|&ip| label(ip).len() + 2)
+            .max()
+            .unwrap_or(0);
+
+        let (reachable, on_loop) = if self.options.annotate {
+            let mut vm = Vm::new(self.program);
+            let result = vm.run();
+            let reachable: Set<isize> = vm.trace().iter().map(|entry| entry.ip).collect();
+            let on_loop: Set<isize> = if result.reason == StopReason::Loop {
+                vm.trace()
+                    .iter()
+                    .map(|entry| entry.ip)
+                    .skip_while(|&ip| ip != result.ip)
+                    .collect()
+            } else {
+                Set::new()
+            };
+            (reachable, on_loop)
+        } else {
+            (Set::new(), Set::new())
+        };
+
+        for (ip, instruction) in self.program.iter().enumerate() {
+            if targets.contains(&ip) {
+                write!(f, "{:<w$}", format!("{}:", label(ip)), w = label_width)?;
+            } else {
+                write!(f, "{:<w$}", "", w = label_width)?;
+            }
+
+            let text = match self.target(ip).filter(|_| self.options.labels) {
+                Some(target) => format!("jmp {}", label(target)),
+                None => instruction.to_string(),
+            };
+            if self.options.annotate {
+                let note = match (
+                    reachable.contains(&(ip as isize)),
+                    on_loop.contains(&(ip as isize)),
+                ) {
+                    (true, true) => "reachable, loop",
+                    (true, false) => "reachable",
+                    (false, _) => "unreachable",
+                };
+                writeln!(f, "{:<12}; {}", text, note)?;
+            } else {
+                writeln!(f, "{}", text)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Pause the execution once the instruction pointer reaches a value or an acc instruction sets the accumulator to a value
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Breakpoint {
+    InstructionPointer(isize),
+    Accumulator(i32),
+}
+
+/// Why the [`Vm`] stopped executing
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum StopReason {
+    /// The instruction pointer is directly behind the last instruction
+    Terminated,
+    /// The next instruction was already executed before
+    Loop,
+    /// The instruction pointer points outside of the program
+    OutOfBounds,
+    /// The breakpoint matched after executing an instruction, running again resumes the execution
+    Breakpoint(Breakpoint),
+}
+
+/// A single executed instruction
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct TraceEntry {
+    ip: isize,
+    instruction: Instruction,
+    /// Accumulator after executing the instruction
+    accumulator: i32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct RunResult {
+    reason: StopReason,
+    ip: isize,
+    accumulator: i32,
+    /// All instructions executed during this run
+    trace: Vec<TraceEntry>,
+}
+
+/// The handheld game console
+struct Vm<'a> {
+    program: &'a [Instruction],
+    ip: isize,
+    accumulator: i32,
+    executed: Vec<bool>,
+    /// Instruction pointers at which the execution pauses
+    ip_breakpoints: Vec<isize>,
+    /// Accumulator values at which the execution pauses
+    accumulator_breakpoints: Vec<i32>,
+    trace: Vec<TraceEntry>,
+}
+
+impl<'a> Vm<'a> {
+    fn new(program: &'a [Instruction]) -> Self {
+        Vm {
+            program,
+            ip: 0,
+            accumulator: 0,
+            executed: vec![false; program.len()],
+            ip_breakpoints: Vec::new(),
+            accumulator_breakpoints: Vec::new(),
+            trace: Vec::new(),
+        }
+    }
+
+    /// Pause the execution when the breakpoint matches
+    ///
+    /// Breakpoints are only checked after executing an instruction, thus they never match before the first one.
+    fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        match breakpoint {
+            Breakpoint::InstructionPointer(ip) => self.ip_breakpoints.push(ip),
+            Breakpoint::Accumulator(accumulator) => self.accumulator_breakpoints.push(accumulator),
+        }
+    }
+
+    /// Continue the execution at `ip`, as if a jump to it was executed
+    fn set_ip(&mut self, ip: isize) {
+        self.ip = ip;
+    }
+
+    /// All instructions executed so far
+    fn trace(&self) -> &[TraceEntry] {
+        &self.trace
+    }
+
+    /// Execute a single instruction
+    ///
+    /// Returns why the execution cannot continue, if the next instruction cannot be executed.
+    fn step(&mut self) -> Option<StopReason> {
+        if self.ip == self.program.len() as isize {
+            return Some(StopReason::Terminated);
+        }
+        if self.ip < 0 || self.ip > self.program.len() as isize {
+            return Some(StopReason::OutOfBounds);
+        }
+        let ip = self.ip as usize;
+        if self.executed[ip] {
+            return Some(StopReason::Loop);
+        }
+        self.executed[ip] = true;
+
+        let instruction = self.program[ip];
+        if instruction.kind == InstructionKind::Acc {
+            self.accumulator += instruction.value;
+        }
+        self.trace.push(TraceEntry {
+            ip: self.ip,
+            instruction,
+            accumulator: self.accumulator,
+        });
+        self.ip = instruction.next_ip(self.ip);
+        None
+    }
+
+    /// Execute instructions until the program stops or a breakpoint is hit
+    fn run(&mut self) -> RunResult {
+        let trace_start = self.trace.len();
+        let reason = loop {
+            if let Some(reason) = self.step() {
+                break reason;
+            }
+            if self.ip_breakpoints.contains(&self.ip) {
+                break StopReason::Breakpoint(Breakpoint::InstructionPointer(self.ip));
+            }
+            // Only an executed acc instruction can change the accumulator
+            let set_accumulator = matches!(
+                self.trace.last(),
+                Some(entry) if entry.instruction.kind == InstructionKind::Acc
+            );
+            if set_accumulator && self.accumulator_breakpoints.contains(&self.accumulator) {
+                break StopReason::Breakpoint(Breakpoint::Accumulator(self.accumulator));
+            }
+        };
+        RunResult {
+            reason,
+            ip: self.ip,
+            accumulator: self.accumulator,
+            trace: self.trace[trace_start..].to_vec(),
+        }
+    }
+}
+
+/// Find the single nop or jmp instruction, which makes the program terminate when flipped
+///
+/// First all instructions are determined, from which the unmodified program terminates, by walking the jumps backwards from the end.
+/// The flipped instruction must be on the original execution path and its flipped successor must be one of those instructions.
+/// This takes linear time in the program length.
+/// Programs which do not loop have nothing to repair.
+fn find_repair(program: &[Instruction]) -> Option<usize> {
+    let len = program.len();
+    // Index `len` is the position directly behind the program
+    let mut predecessors = vec![Vec::new(); len + 1];
+    for (ip, instruction) in program.iter().enumerate() {
+        let next = instruction.next_ip(ip as isize);
+        if 0 <= next && next <= len as isize {
+            predecessors[next as usize].push(ip);
+        }
+    }
+    let mut terminates = vec![false; len + 1];
+    terminates[len] = true;
+    let mut stack = vec![len];
+    while let Some(ip) = stack.pop() {
+        for &pred in &predecessors[ip] {
+            if !terminates[pred] {
+                terminates[pred] = true;
+                stack.push(pred);
+            }
+        }
+    }
+
+    let mut vm = Vm::new(program);
+    if vm.run().reason != StopReason::Loop {
+        return None;
+    }
+    vm.trace().iter().find_map(|entry| {
+        let next = entry.instruction.flipped()?.next_ip(entry.ip);
+        if 0 <= next && next <= len as isize && terminates[next as usize] {
+            Some(entry.ip as usize)
+        } else {
+            None
+        }
+    })
+}
+
 #[aoc(day8, part1)]
 fn part1(input: &[Instruction]) -> i32 {
-    if let RunResult::Loop(value) = run(input) {
-        value
+    let result = Vm::new(input).run();
+    if result.reason == StopReason::Loop {
+        result.accumulator
     } else {
-        panic!("The code should loop but it didn't")
+        panic!(
+            "The code should loop but it didn't:\n{}",
+            Listing::new(
+                input,
+                ListingOptions {
+                    labels: true,
+                    annotate: true,
+                }
+            )
+        )
     }
 }
 
 #[aoc(day8, part2)]
 fn part2(input: &[Instruction]) -> i32 {
+    let idx = find_repair(input).unwrap_or_else(|| {
+        panic!(
+            "Should have found a modification which makes the program terminate:\n{}",
+            Listing::new(
+                input,
+                ListingOptions {
+                    labels: true,
+                    annotate: true,
+                }
+            )
+        )
+    });
+    // The instructions from the flipped successor to the end are not on the original execution path.
+    // Thus, run the original program up to the repaired instruction and continue as if it was flipped.
+    let mut vm = Vm::new(input);
+    if idx != 0 {
+        let breakpoint = Breakpoint::InstructionPointer(idx as isize);
+        vm.add_breakpoint(breakpoint);
+        assert_eq!(StopReason::Breakpoint(breakpoint), vm.run().reason);
+    }
+    vm.set_ip(input[idx].flipped().unwrap().next_ip(idx as isize));
+    let result = vm.run();
+    assert_eq!(StopReason::Terminated, result.reason);
+    result.accumulator
+}
+
+#[aoc(day8, part2, brute_force)]
+fn part2_brute_force(input: &[Instruction]) -> i32 {
     // Simply try all modifications for nop->jmp and jmp->nop to see which terminates
     for i in 0..input.len() {
         let mut input = input.to_vec();
-        input[i].kind = match input[i].kind {
-            InstructionKind::Acc => continue,
-            InstructionKind::Nop => InstructionKind::Jmp,
-            InstructionKind::Jmp => InstructionKind::Nop,
+        input[i] = match input[i].flipped() {
+            Some(instruction) => instruction,
+            None => continue,
         };
 
-        if let RunResult::Terminate(value) = run(&input) {
-            println!("{}", i);
-            return value;
+        let result = Vm::new(&input).run();
+        if result.reason == StopReason::Terminated {
+            return result.accumulator;
         }
     }
     panic!("Should have found a modification which makes the program terminate")
 }
 
-enum RunResult {
-    Loop(i32),
-    Terminate(i32),
-    Error,
-}
-
-fn run(input: &[Instruction]) -> RunResult {
-    let mut executed_instructions = Set::<usize>::new();
-    let mut instruction_pointer = 0;
-    let mut accumulator = 0;
-
-    loop {
-        if !executed_instructions.insert(instruction_pointer) {
-            // Value was already included in the set
-            return RunResult::Loop(accumulator);
-        } else if instruction_pointer == input.len() {
-            return RunResult::Terminate(accumulator);
-        } else if instruction_pointer > input.len() {
-            println!("Found error");
-            return RunResult::Error;
-        }
-        let Instruction { kind, value } = &input[instruction_pointer];
-        match kind {
-            InstructionKind::Acc => accumulator += value,
-            InstructionKind::Nop => {}
-            InstructionKind::Jmp => {
-                instruction_pointer = (instruction_pointer as i32 + value) as usize;
-                continue;
-            }
-        };
-        instruction_pointer += 1;
-    }
-}
-
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(5, part1(&values));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day8.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day8.txt").trim()).unwrap();
     assert_eq!(1137, part1(&values));
 }
 
 #[test]
 fn test_part2() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(8, part2(&values));
 }
 
 #[test]
 fn test_part2_solution() {
-    let values = input_generator(include_str!("../input/2020/day8.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day8.txt").trim()).unwrap();
     assert_eq!(1125, part2(&values));
 }
 
+#[test]
+fn test_part2_brute_force() {
+    let values = input_generator(PUZZLE).unwrap();
+    assert_eq!(8, part2_brute_force(&values));
+    let values = input_generator(include_str!("../input/2020/day8.txt").trim()).unwrap();
+    assert_eq!(1125, part2_brute_force(&values));
+}
+
+#[test]
+fn test_find_repair() {
+    let values = input_generator(PUZZLE).unwrap();
+    assert_eq!(Some(7), find_repair(&values));
+    // Already terminating programs have no loop to repair
+    let values = input_generator("nop +0\njmp +2\nacc +1").unwrap();
+    assert_eq!(None, find_repair(&values));
+}
+
+#[test]
+fn test_vm_trace_and_breakpoints() {
+    let values = input_generator(PUZZLE).unwrap();
+    let mut vm = Vm::new(&values);
+    vm.add_breakpoint(Breakpoint::InstructionPointer(6));
+    vm.add_breakpoint(Breakpoint::InstructionPointer(3));
+
+    let result = vm.run();
+    assert_eq!(
+        StopReason::Breakpoint(Breakpoint::InstructionPointer(6)),
+        result.reason
+    );
+    assert_eq!(6, result.ip);
+    assert_eq!(
+        vec![0, 1, 2],
+        result.trace.iter().map(|entry| entry.ip).collect_vec()
+    );
+    assert_eq!(1, result.accumulator);
+
+    let result = vm.run();
+    assert_eq!(
+        StopReason::Breakpoint(Breakpoint::InstructionPointer(3)),
+        result.reason
+    );
+    assert_eq!(
+        vec![6, 7],
+        result.trace.iter().map(|entry| entry.ip).collect_vec()
+    );
+    assert_eq!(2, result.accumulator);
+
+    let result = vm.run();
+    assert_eq!(StopReason::Loop, result.reason);
+    assert_eq!(1, result.ip);
+    assert_eq!(5, result.accumulator);
+    assert_eq!(2, result.trace.len());
+    assert_eq!(7, vm.trace().len());
+}
+
+#[test]
+fn test_vm_accumulator_breakpoint() {
+    let values = input_generator(PUZZLE).unwrap();
+    let mut vm = Vm::new(&values);
+    vm.add_breakpoint(Breakpoint::Accumulator(1));
+    vm.add_breakpoint(Breakpoint::Accumulator(5));
+
+    let result = vm.run();
+    assert_eq!(
+        StopReason::Breakpoint(Breakpoint::Accumulator(1)),
+        result.reason
+    );
+    assert_eq!(
+        vec![0, 1],
+        result.trace.iter().map(|entry| entry.ip).collect_vec()
+    );
+    assert_eq!(2, result.ip);
+
+    // Instructions which do not set the accumulator do not match, even though it still holds the value
+    let result = vm.run();
+    assert_eq!(
+        StopReason::Breakpoint(Breakpoint::Accumulator(5)),
+        result.reason
+    );
+    assert_eq!(
+        vec![2, 6, 7, 3],
+        result.trace.iter().map(|entry| entry.ip).collect_vec()
+    );
+    assert_eq!(5, result.accumulator);
+
+    assert_eq!(StopReason::Loop, vm.run().reason);
+}
+
+#[test]
+fn test_part2_repair_first_instruction() {
+    let values = input_generator("jmp +0\nacc +2\nacc +3").unwrap();
+    assert_eq!(Some(0), find_repair(&values));
+    assert_eq!(5, part2(&values));
+}
+
+#[test]
+fn test_vm_out_of_bounds() {
+    let values = input_generator("acc +1\njmp -2").unwrap();
+    let mut vm = Vm::new(&values);
+    assert_eq!(None, vm.step());
+    assert_eq!(None, vm.step());
+    assert_eq!(Some(StopReason::OutOfBounds), vm.step());
+}
+
+#[test]
+fn test_listing() {
+    let values = input_generator(PUZZLE).unwrap();
+    assert_eq!(
+        PUZZLE,
+        Listing::new(&values, ListingOptions::default())
+            .to_string()
+            .trim_end()
+    );
+    let listing = Listing::new(
+        &values,
+        ListingOptions {
+            labels: true,
+            annotate: true,
+        },
+    );
+    assert_eq!(
+        r#"    nop +0      ; reachable
+L1: acc +1      ; reachable, loop
+    jmp L6      ; reachable, loop
+L3: acc +3      ; reachable, loop
+    jmp L1      ; reachable, loop
+    acc -99     ; unreachable
+L6: acc +1      ; reachable, loop
+    jmp L3      ; reachable, loop
+    acc +6      ; unreachable
+"#,
+        listing.to_string()
+    );
+}
+
+/// Parsing any printed program must result in the same program
+#[test]
+fn test_listing_round_trip() {
+    // Simple xorshift generator, such that the test is deterministic
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut random = |bound: u64| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % bound
+    };
+
+    for _ in 0..200 {
+        let len = 1 + random(40) as i32;
+        let program = (0..len)
+            .map(|_| {
+                let kind = match random(3) {
+                    0 => InstructionKind::Acc,
+                    1 => InstructionKind::Nop,
+                    _ => InstructionKind::Jmp,
+                };
+                let value = random(4 * len as u64) as i32 - 2 * len;
+                Instruction { kind, value }
+            })
+            .collect_vec();
+
+        for &labels in &[false, true] {
+            for &annotate in &[false, true] {
+                let options = ListingOptions { labels, annotate };
+                let text = Listing::new(&program, options).to_string();
+                assert_eq!(
+                    Ok(&program),
+                    input_generator(&text).as_ref(),
+                    "Listing with {:?} does not round-trip:\n{}",
+                    options,
+                    text
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_parse_labels() {
+    let values = input_generator("start: acc +1\n  jmp start ; back\nend: jmp end").unwrap();
+    assert_eq!(
+        vec![
+            Instruction {
+                kind: InstructionKind::Acc,
+                value: 1
+            },
+            Instruction {
+                kind: InstructionKind::Jmp,
+                value: -1
+            },
+            Instruction {
+                kind: InstructionKind::Jmp,
+                value: 0
+            },
+        ],
+        values
+    );
+    let err = input_generator("a: nop +0\na: nop +0").unwrap_err();
+    assert_eq!((2, 1), (err.line, err.column));
+    let err = input_generator("jmp nowhere").unwrap_err();
+    assert_eq!((1, 5), (err.line, err.column));
+}
+
 #[cfg(test)]
 static PUZZLE: &str = r#"nop +0
 acc +1
@@ -2,11 +2,9 @@ use crate::prelude::*;
 use std::{cmp::Ordering, collections::VecDeque};
 
 #[aoc_generator(day9)]
-fn input_generator(input: &str) -> Vec<u64> {
-    input
-        .split('\n')
-        .map(|line| line.parse().unwrap())
-        .collect()
+fn input_generator(input: &str) -> Result<Vec<u64>, ParseError> {
+    let ctx = ParseContext::new(9, input);
+    input.split('\n').map(|line| ctx.parse(line)).collect()
 }
 
 #[aoc(day9, part1)]
@@ -73,25 +71,25 @@ fn find_range(input: &[u64], target: u64) -> u64 {
 
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(127, find_number(&values, 5));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day9.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day9.txt").trim()).unwrap();
     assert_eq!(69316178, part1(&values));
 }
 
 #[test]
 fn test_part2() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(62, find_range(&values, 127));
 }
 
 #[test]
 fn test_part2_solution() {
-    let values = input_generator(include_str!("../input/2020/day9.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day9.txt").trim()).unwrap();
     assert_eq!(9351526, part2(&values));
 }
 
@@ -1,98 +1,132 @@
+use crate::adapters::{AdapterChain, ChainError};
 use crate::prelude::*;
+use num_bigint::BigUint;
+use std::error::Error;
+use std::fmt::Write;
 
 #[aoc_generator(day10)]
-fn input_generator(input: &str) -> Vec<u32> {
-    let mut input: Vec<_> = input
-        .split('\n')
-        .map(|line| line.parse().unwrap())
-        .collect();
-    input.push(0);
-    input.sort_unstable();
-    input.push(input[input.len() - 1] + 3);
-    input
+fn input_generator(input: &str) -> Result<Vec<u32>, ParseError> {
+    let ctx = ParseContext::new(10, input);
+    input.split('\n').map(|line| ctx.parse(line)).collect()
 }
 
 #[aoc(day10, part1)]
-fn part1(input: &[u32]) -> u32 {
-    let (a, b) = joltage_differences(input);
-    a * b
-}
-
-fn joltage_differences(input: &[u32]) -> (u32, u32) {
-    input
-        .iter()
-        .tuple_windows()
-        .map(|(a, b)| b - a)
-        .fold((0, 0), |(diff_1, diff_3), diff| {
-            if diff == 1 {
-                (diff_1 + 1, diff_3)
-            } else {
-                (diff_1, diff_3 + 1)
-            }
-        })
+fn part1(input: &[u32]) -> Result<usize, ChainError> {
+    let histogram = AdapterChain::new(input, 3)?.gap_histogram();
+    let gap = |size| histogram.get(&size).copied().unwrap_or(0);
+    Ok(gap(1) * gap(3))
 }
 
 #[aoc(day10, part2)]
-fn part2(input: &[u32]) -> u64 {
-    charger_variations(input)
+fn part2(input: &[u32]) -> Result<BigUint, ChainError> {
+    Ok(AdapterChain::new(input, 3)?.count().clone())
 }
 
-fn charger_variations(input: &[u32]) -> u64 {
-    fn charger_variations_inner(mut input: &[u32], cache: &mut Map<usize, u64>) -> u64 {
-        let orig_len = input.len();
-        if let Some(res) = cache.get(&orig_len) {
-            return *res;
-        }
-
-        let first = input[0];
-        input = &input[1..];
-
-        let mut variations = 0;
-        while !input.is_empty() && (input[0] - first) <= 3 {
-            variations += charger_variations_inner(input, cache);
-            input = &input[1..];
-        }
-        cache.insert(orig_len, variations);
-        variations
+/// List the first arrangements in lexicographic order and a few random ones as text
+pub fn arrangements(
+    input: &str,
+) -> Result<Vec<(&'static str, String)>, Box<dyn Error + Send + Sync>> {
+    let chain = AdapterChain::new(&input_generator(input)?, 3)?;
+    let mut text = format!("{} arrangements\n\nFirst arrangements:\n", chain.count());
+    for arrangement in chain.arrangements().take(5) {
+        writeln!(text, "{}", arrangement.iter().join(" ")).unwrap();
+    }
+    text.push_str("\nRandom arrangements:\n");
+    for seed in 0..5 {
+        writeln!(text, "{}", chain.sample(seed).iter().join(" ")).unwrap();
     }
-    let mut cache: Map<usize, u64> = vec![(1, 1)].into_iter().collect();
-    charger_variations_inner(input, &mut cache)
+    Ok(vec![("arrangements", text)])
+}
+
+#[cfg(test)]
+fn chain(input: &str) -> AdapterChain {
+    AdapterChain::new(&input_generator(input).unwrap(), 3).unwrap()
 }
 
 #[test]
 fn test_part1_small() {
-    let values = input_generator(PUZZLE_SMALL);
-    assert_eq!((7, 5), joltage_differences(&values));
+    let histogram = chain(PUZZLE_SMALL).gap_histogram();
+    assert_eq!(vec![(1, 7), (3, 5)], histogram.into_iter().collect_vec());
 }
 
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
-    assert_eq!((22, 10), joltage_differences(&values));
+    let histogram = chain(PUZZLE).gap_histogram();
+    assert_eq!(vec![(1, 22), (3, 10)], histogram.into_iter().collect_vec());
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day10.txt").trim());
-    assert_eq!(1755, part1(&values));
+    let values = input_generator(include_str!("../input/2020/day10.txt").trim()).unwrap();
+    assert_eq!(Ok(1755), part1(&values));
 }
 
 #[test]
 fn test_part2_small() {
-    let values = input_generator(PUZZLE_SMALL);
-    assert_eq!(8, charger_variations(&values));
+    assert_eq!(&BigUint::from(8_u32), chain(PUZZLE_SMALL).count());
 }
 
 #[test]
 fn test_part2() {
-    let values = input_generator(PUZZLE);
-    assert_eq!(19208, charger_variations(&values));
+    assert_eq!(&BigUint::from(19208_u32), chain(PUZZLE).count());
 }
 
 #[test]
 fn test_part2_solution() {
-    let values = input_generator(include_str!("../input/2020/day10.txt").trim());
-    assert_eq!(4049565169664, part2(&values));
+    let values = input_generator(include_str!("../input/2020/day10.txt").trim()).unwrap();
+    assert_eq!(Ok(BigUint::from(4049565169664_u64)), part2(&values));
+}
+
+#[test]
+fn test_larger_gaps() {
+    // With a maximum gap of 4, more adapters can be skipped and the device moves up by one
+    let chain = AdapterChain::new(&input_generator(PUZZLE_SMALL).unwrap(), 4).unwrap();
+    assert_eq!(&BigUint::from(170_u32), chain.count());
+    assert_eq!(
+        vec![(1, 7), (3, 4), (4, 1)],
+        chain.gap_histogram().into_iter().collect_vec()
+    );
+    let values = input_generator("1\n5").unwrap();
+    assert!(part1(&values).is_err());
+}
+
+#[test]
+fn test_arrangements() {
+    let files = arrangements(PUZZLE_SMALL).unwrap();
+    assert_eq!(
+        vec!["arrangements"],
+        files.iter().map(|(name, _)| *name).collect_vec()
+    );
+    let lines = files[0].1.lines().collect_vec();
+    assert_eq!(
+        vec![
+            "8 arrangements",
+            "",
+            "First arrangements:",
+            "1 4 5 6 7 10 11 12 15 16 19",
+            "1 4 5 6 7 10 12 15 16 19",
+            "1 4 5 7 10 11 12 15 16 19",
+            "1 4 5 7 10 12 15 16 19",
+            "1 4 6 7 10 11 12 15 16 19",
+            "",
+            "Random arrangements:",
+        ],
+        lines[..10]
+    );
+    let all = chain(PUZZLE_SMALL)
+        .arrangements()
+        .map(|arrangement| arrangement.iter().join(" "))
+        .collect_vec();
+    assert!(lines[10..]
+        .iter()
+        .all(|sample| all.contains(&sample.to_string())));
+    assert_eq!(15, lines.len());
+
+    let err = arrangements("1\n5").unwrap_err();
+    assert_eq!(
+        "the device cannot be reached, the gap from 1 to 5 jolts is larger than 3",
+        err.to_string()
+    );
 }
 
 #[cfg(test)]
@@ -1,301 +1,312 @@
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+use crate::animation::Animation;
+use crate::grid::{Grid, GridCell, Neighborhood};
+use crate::prelude::*;
+
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 enum State {
     Floor,
     Empty,
     Occupied,
 }
 
-#[aoc_generator(day11)]
-fn input_generator(input: &str) -> Vec<Vec<State>> {
-    input
-        .split('\n')
-        .map(|line| {
-            line.chars()
-                .map(|c| match c {
-                    '.' => State::Floor,
-                    'L' => State::Empty,
-                    '#' => State::Occupied,
-                    _ => panic!("Unknown state symbol {}", c),
-                })
-                .collect()
-        })
-        .collect()
-}
+impl GridCell for State {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '.' => Some(State::Floor),
+            'L' => Some(State::Empty),
+            '#' => Some(State::Occupied),
+            _ => None,
+        }
+    }
 
-#[allow(dead_code)]
-fn print_layout(layout: &[Vec<State>]) {
-    for line in layout {
-        for place in line {
-            eprint!(
-                "{}",
-                match place {
-                    State::Floor => '.',
-                    State::Empty => 'L',
-                    State::Occupied => '#',
-                }
-            )
+    fn to_char(&self) -> char {
+        match self {
+            State::Floor => '.',
+            State::Empty => 'L',
+            State::Occupied => '#',
         }
-        eprintln!();
     }
-    eprintln!();
 }
 
-#[aoc(day11, part1)]
-fn part1(input: &[Vec<State>]) -> usize {
-    let size_x = input.len();
-    let size_y = input[0].len();
+#[aoc_generator(day11)]
+fn input_generator(input: &str) -> Result<Grid<State>, ParseError> {
+    Grid::parse(&ParseContext::new(11, input), input)
+}
 
-    let mut curr = input.to_vec();
-    let mut next = input.to_vec();
+/// Which seats are considered as neighbors of a seat
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum NeighborRule {
+    /// The up to eight directly adjacent seats
+    Adjacent,
+    /// The first seat visible in each of the eight directions, looking past the floor
+    FirstVisible,
+}
 
-    loop {
-        // let is_empty_seat = |x: usize, y: usize| if curr[x][y] == State::Empty { 1 } else { 0 };
-        let is_occupied_seat =
-            |x: usize, y: usize| if curr[x][y] == State::Occupied { 1 } else { 0 };
-        for x in 0..size_x {
-            for y in 0..size_y {
-                // Floors never change
-                let field = curr[x][y];
-                if field == State::Floor {
-                    continue;
-                }
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct Rules {
+    neighbors: NeighborRule,
+    /// Occupied seats become empty if at least this many neighbors are occupied
+    tolerance: usize,
+}
 
-                let mut occupied_count = 0;
+static PART1: Rules = Rules {
+    neighbors: NeighborRule::Adjacent,
+    tolerance: 4,
+};
+static PART2: Rules = Rules {
+    neighbors: NeighborRule::FirstVisible,
+    tolerance: 5,
+};
 
-                // Check left-above
-                if x > 0 && y > 0 {
-                    occupied_count += is_occupied_seat(x - 1, y - 1)
-                }
-                // Check above
-                if x > 0 {
-                    occupied_count += is_occupied_seat(x - 1, y)
-                }
-                // Check right-above
-                if x > 0 && y < size_y - 1 {
-                    occupied_count += is_occupied_seat(x - 1, y + 1)
-                }
-                // Check left
-                if y > 0 {
-                    occupied_count += is_occupied_seat(x, y - 1)
-                }
-                // Check right
-                if y < size_y - 1 {
-                    occupied_count += is_occupied_seat(x, y + 1)
-                }
-                // Check left-bottom
-                if x < size_x - 1 && y > 0 {
-                    occupied_count += is_occupied_seat(x + 1, y - 1)
-                }
-                // Check bottom
-                if x < size_x - 1 {
-                    occupied_count += is_occupied_seat(x + 1, y)
-                }
-                // Check right-bottom
-                if x < size_x - 1 && y < size_y - 1 {
-                    occupied_count += is_occupied_seat(x + 1, y + 1)
-                }
+/// Simulation of the seats only, with the neighbors of each seat computed once
+#[derive(Clone, Debug)]
+struct Seating {
+    width: usize,
+    height: usize,
+    /// Grid position of every seat
+    seats: Vec<(usize, usize)>,
+    /// Indices of the neighbor seats of every seat
+    neighbors: Vec<Vec<usize>>,
+    tolerance: usize,
+    occupied: Vec<bool>,
+    /// Buffer for the next generation, swapped with `occupied` after each step
+    next: Vec<bool>,
+}
 
-                if field == State::Empty && occupied_count == 0 {
-                    next[x][y] = State::Occupied
-                } else if field == State::Occupied && occupied_count >= 4 {
-                    next[x][y] = State::Empty
-                } else {
-                    next[x][y] = field
-                }
+impl Seating {
+    fn new(layout: &Grid<State>, rules: Rules) -> Self {
+        let seats = layout
+            .positions()
+            .filter(|&pos| layout[pos] != State::Floor)
+            .collect_vec();
+        let mut seat_index = Grid::from_fn(layout.width(), layout.height(), |_| None);
+        for (idx, &pos) in seats.iter().enumerate() {
+            seat_index[pos] = Some(idx);
+        }
+
+        let neighbors = seats
+            .iter()
+            .map(|&pos| {
+                Neighborhood::Eight
+                    .offsets()
+                    .iter()
+                    .filter_map(|&direction| match rules.neighbors {
+                        NeighborRule::Adjacent => layout.offset(pos, direction),
+                        NeighborRule::FirstVisible => {
+                            layout.ray_cast(pos, direction, |&s| s == State::Floor)
+                        }
+                    })
+                    .filter_map(|pos| seat_index[pos])
+                    .collect()
+            })
+            .collect();
+        let occupied = seats
+            .iter()
+            .map(|&pos| layout[pos] == State::Occupied)
+            .collect_vec();
+
+        Seating {
+            width: layout.width(),
+            height: layout.height(),
+            seats,
+            neighbors,
+            tolerance: rules.tolerance,
+            next: occupied.clone(),
+            occupied,
+        }
+    }
+
+    fn next_state(&self, seat: usize) -> bool {
+        let count = self.neighbors[seat]
+            .iter()
+            .filter(|&&other| self.occupied[other])
+            .count();
+        if self.occupied[seat] {
+            count < self.tolerance
+        } else {
+            count == 0
+        }
+    }
+
+    /// Advance by one generation, returning whether any seat changed
+    fn step(&mut self, parallel: bool) -> bool {
+        let mut next = std::mem::take(&mut self.next);
+        if parallel {
+            next.par_iter_mut()
+                .enumerate()
+                .for_each(|(seat, state)| *state = self.next_state(seat));
+        } else {
+            for (seat, state) in next.iter_mut().enumerate() {
+                *state = self.next_state(seat);
             }
         }
+        let changed = next != self.occupied;
+        self.next = std::mem::replace(&mut self.occupied, next);
+        changed
+    }
 
-        if next == curr {
-            break;
+    /// Apply the seating rules until the layout no longer changes and return the number of generations
+    fn run_until_stable(&mut self, parallel: bool) -> usize {
+        let mut generations = 0;
+        while self.step(parallel) {
+            generations += 1;
         }
-        std::mem::swap(&mut next, &mut curr);
+        generations
     }
 
-    curr.into_iter()
-        .flatten()
-        .filter(|&s| s == State::Occupied)
-        .count()
+    fn occupied_count(&self) -> usize {
+        self.occupied.iter().filter(|&&occupied| occupied).count()
+    }
+
+    /// The current layout including the floor
+    fn layout(&self) -> Grid<State> {
+        let mut layout = Grid::from_fn(self.width, self.height, |_| State::Floor);
+        for (&pos, &occupied) in self.seats.iter().zip(&self.occupied) {
+            layout[pos] = if occupied {
+                State::Occupied
+            } else {
+                State::Empty
+            };
+        }
+        layout
+    }
 }
 
-#[aoc(day11, part2)]
-fn part2(input: &[Vec<State>]) -> usize {
-    let size_x = input.len();
-    let size_y = input[0].len();
+fn solve(input: &Grid<State>, rules: Rules, parallel: bool) -> usize {
+    let mut seating = Seating::new(input, rules);
+    seating.run_until_stable(parallel);
+    seating.occupied_count()
+}
 
-    let mut curr = input.to_vec();
-    let mut next = input.to_vec();
+#[aoc(day11, part1)]
+fn part1(input: &Grid<State>) -> usize {
+    solve(input, PART1, false)
+}
 
-    loop {
-        // print_layout(&curr);
-        for x in 0..size_x {
-            for y in 0..size_y {
-                // Floors never change
-                let field = curr[x][y];
-                if field == State::Floor {
-                    continue;
-                }
+#[aoc(day11, part1, parallel)]
+fn part1_parallel(input: &Grid<State>) -> usize {
+    solve(input, PART1, true)
+}
 
-                let mut occupied_count = 0;
+#[aoc(day11, part2)]
+fn part2(input: &Grid<State>) -> usize {
+    solve(input, PART2, false)
+}
 
-                // Check left-above
-                {
-                    let mut x = x;
-                    let mut y = y;
-                    while x > 0 && y > 0 {
-                        x -= 1;
-                        y -= 1;
-                        match curr[x][y] {
-                            State::Occupied => occupied_count += 1,
-                            State::Empty => {}
-                            State::Floor => continue,
-                        }
-                        break;
-                    }
-                }
-                // Check above
-                {
-                    let mut x = x;
-                    while x > 0 {
-                        x -= 1;
-                        match curr[x][y] {
-                            State::Occupied => occupied_count += 1,
-                            State::Empty => {}
-                            State::Floor => continue,
-                        }
-                        break;
-                    }
-                }
-                // Check right-above
-                {
-                    let mut x = x;
-                    let mut y = y;
-                    while x > 0 && y < size_y - 1 {
-                        x -= 1;
-                        y += 1;
-                        match curr[x][y] {
-                            State::Occupied => occupied_count += 1,
-                            State::Empty => {}
-                            State::Floor => continue,
-                        }
-                        break;
-                    }
-                }
-                // Check left
-                {
-                    let mut y = y;
-                    while y > 0 {
-                        y -= 1;
-                        match curr[x][y] {
-                            State::Occupied => occupied_count += 1,
-                            State::Empty => {}
-                            State::Floor => continue,
-                        }
-                        break;
-                    }
-                }
-                // Check right
-                {
-                    let mut y = y;
-                    while y < size_y - 1 {
-                        y += 1;
-                        match curr[x][y] {
-                            State::Occupied => occupied_count += 1,
-                            State::Empty => {}
-                            State::Floor => continue,
-                        }
-                        break;
-                    }
-                }
-                // Check left-bottom
-                {
-                    let mut x = x;
-                    let mut y = y;
-                    while x < size_x - 1 && y > 0 {
-                        x += 1;
-                        y -= 1;
-                        match curr[x][y] {
-                            State::Occupied => occupied_count += 1,
-                            State::Empty => {}
-                            State::Floor => continue,
-                        }
-                        break;
-                    }
-                }
-                // Check bottom
-                {
-                    let mut x = x;
-                    while x < size_x - 1 {
-                        x += 1;
-                        match curr[x][y] {
-                            State::Occupied => occupied_count += 1,
-                            State::Empty => {}
-                            State::Floor => continue,
-                        }
-                        break;
-                    }
-                }
-                // Check right-bottom
-                {
-                    let mut x = x;
-                    let mut y = y;
-                    while x < size_x - 1 && y < size_y - 1 {
-                        x += 1;
-                        y += 1;
-                        match curr[x][y] {
-                            State::Occupied => occupied_count += 1,
-                            State::Empty => {}
-                            State::Floor => continue,
-                        }
-                        break;
-                    }
-                }
+#[aoc(day11, part2, parallel)]
+fn part2_parallel(input: &Grid<State>) -> usize {
+    solve(input, PART2, true)
+}
 
-                if field == State::Empty && occupied_count == 0 {
-                    next[x][y] = State::Occupied
-                } else if field == State::Occupied && occupied_count >= 5 {
-                    next[x][y] = State::Empty
-                } else {
-                    next[x][y] = field
+/// Record every generation of both parts, until the layout is stable
+pub fn animations(input: &str) -> Result<Vec<(&'static str, Animation)>, ParseError> {
+    let layout = input_generator(input)?;
+    let palette = [
+        ('.', [40, 40, 40]),
+        ('L', [60, 160, 60]),
+        ('#', [220, 60, 40]),
+    ];
+    Ok(vec![("part1", PART1), ("part2", PART2)]
+        .into_iter()
+        .map(|(name, rules)| {
+            let mut seating = Seating::new(&layout, rules);
+            let mut animation = Animation::new(&palette);
+            animation.push("Generation 0", &seating.layout());
+            for generation in 1.. {
+                if !seating.step(false) {
+                    break;
                 }
+                animation.push(format!("Generation {}", generation), &seating.layout());
             }
-        }
-
-        if next == curr {
-            break;
-        }
-        std::mem::swap(&mut next, &mut curr);
-    }
-
-    curr.into_iter()
-        .flatten()
-        .filter(|&s| s == State::Occupied)
-        .count()
+            (name, animation)
+        })
+        .collect())
 }
 
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(37, part1(&values));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day11.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day11.txt").trim()).unwrap();
     assert_eq!(2489, part1(&values));
 }
 
 #[test]
 fn test_part2() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(26, part2(&values));
 }
 
 #[test]
 fn test_part2_solution() {
-    let values = input_generator(include_str!("../input/2020/day11.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day11.txt").trim()).unwrap();
     assert_eq!(2180, part2(&values));
 }
 
+#[test]
+fn test_parallel() {
+    let values = input_generator(include_str!("../input/2020/day11.txt").trim()).unwrap();
+    assert_eq!(2489, part1_parallel(&values));
+    assert_eq!(2180, part2_parallel(&values));
+}
+
+#[test]
+fn test_generations() {
+    let values = input_generator(PUZZLE).unwrap();
+    let mut seating = Seating::new(&values, PART1);
+    seating.step(false);
+    seating.step(false);
+    assert_eq!(
+        "#.LL.L#.##
+#LLLLLL.L#
+L.L.L..L..
+#LLL.LL.L#
+#.LL.LL.LL
+#.LLLL#.##
+..L.L.....
+#LLLLLLLL#
+#.LLLLLL.L
+#.#LLLL.##
+",
+        seating.layout().to_string()
+    );
+    // Two generations were already done, then the layout changes three more times
+    assert_eq!(3, seating.run_until_stable(false));
+
+    // A larger tolerance with the adjacent rule
+    let rules = Rules {
+        tolerance: 5,
+        ..PART1
+    };
+    let mut seating = Seating::new(&values, rules);
+    seating.run_until_stable(true);
+    let mut sequential = Seating::new(&values, rules);
+    sequential.run_until_stable(false);
+    assert_eq!(sequential.layout(), seating.layout());
+}
+
+#[test]
+fn test_animations() {
+    let animations = animations(PUZZLE).unwrap();
+    assert_eq!(
+        vec!["part1", "part2"],
+        animations.iter().map(|(name, _)| *name).collect_vec()
+    );
+    // The initial layout plus five changing generations for part 1, six for part 2
+    let log = animations[0].1.text_log();
+    assert!(log.starts_with("Generation 0\nL.LL.LL.LL\n"));
+    assert!(log.contains("Generation 5\n#.#L.L#.##\n"));
+    assert!(!log.contains("Generation 6"));
+    assert!(animations[1]
+        .1
+        .text_log()
+        .contains("Generation 6\n#.L#.L#.L#\n"));
+}
+
 #[cfg(test)]
 static PUZZLE: &str = r#"L.LL.LL.LL
 LLLLLLL.LL
@@ -1,41 +1,7 @@
-#[derive(Debug)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
-impl Direction {
-    fn vector(&self) -> (i32, i32) {
-        match *self {
-            Direction::North => (0, 1),
-            Direction::East => (1, 0),
-            Direction::South => (0, -1),
-            Direction::West => (-1, 0),
-        }
-    }
-
-    fn left(&mut self) {
-        *self = match self {
-            Direction::North => Direction::West,
-            Direction::East => Direction::North,
-            Direction::South => Direction::East,
-            Direction::West => Direction::South,
-        };
-    }
+use crate::prelude::*;
+use crate::svg::Svg;
 
-    fn right(&mut self) {
-        *self = match self {
-            Direction::North => Direction::East,
-            Direction::East => Direction::South,
-            Direction::South => Direction::West,
-            Direction::West => Direction::North,
-        };
-    }
-}
-
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 enum Action {
     North,
     East,
@@ -47,12 +13,12 @@ enum Action {
 }
 
 #[aoc_generator(day12)]
-fn input_generator(input: &str) -> Vec<(Action, i32)> {
+fn input_generator(input: &str) -> Result<Vec<(Action, i32)>, ParseError> {
+    let ctx = ParseContext::new(12, input);
     input
         .split('\n')
         .map(|line| {
-            let action = &line[0..1];
-            let value = &line[1..];
+            let (action, value) = line.split_at(line.chars().next().map_or(0, char::len_utf8));
             let action = match action {
                 "N" => Action::North,
                 "E" => Action::East,
@@ -61,105 +27,331 @@ fn input_generator(input: &str) -> Vec<(Action, i32)> {
                 "L" => Action::Left,
                 "R" => Action::Right,
                 "F" => Action::Forward,
-                _ => panic!("Unknown action {}", action),
+                _ => return Err(ctx.error(action, "unknown action")),
             };
-            let value = value.parse().unwrap();
-            (action, value)
+            let parsed: i32 = ctx.parse(value)?;
+            if matches!(action, Action::Left | Action::Right) && parsed % 90 != 0 {
+                return Err(ctx.error(value, "turns must be multiples of 90 degrees"));
+            }
+            Ok((action, parsed))
         })
         .collect()
 }
 
-#[aoc(day12, part1)]
-fn part1(input: &[(Action, i32)]) -> i32 {
-    let mut direction = Direction::East;
-    // (east, north)
-    let mut position: (i32, i32) = (0, 0);
-
-    for (action, value) in input {
-        match *action {
-            Action::North => position.1 += value,
-            Action::East => position.0 += value,
-            Action::South => position.1 -= value,
-            Action::West => position.0 -= value,
-            Action::Left => {
-                for _ in 0..(value / 90) {
-                    direction.left();
-                }
-            }
-            Action::Right => {
-                for _ in 0..(value / 90) {
-                    direction.right()
-                }
-            }
+/// Whether the cardinal actions move the ship or the waypoint
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Mode {
+    /// The ship moves itself and `F` moves along its heading
+    Heading,
+    /// The ship moves a waypoint relative to itself and `F` moves towards the waypoint
+    Waypoint,
+}
+
+/// Position of the ship and its vector after an action
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct Step {
+    /// `(east, north)`
+    position: (i32, i32),
+    /// The heading or the waypoint offset, depending on the mode
+    vector: (i32, i32),
+}
+
+impl Step {
+    fn manhattan_distance(&self) -> i32 {
+        self.position.0.abs() + self.position.1.abs()
+    }
+
+    /// Absolute position of the waypoint
+    fn waypoint(&self) -> (i32, i32) {
+        (
+            self.position.0 + self.vector.0,
+            self.position.1 + self.vector.1,
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Ship {
+    mode: Mode,
+    /// The start followed by the state after every action
+    path: Vec<Step>,
+}
+
+impl Ship {
+    fn new(mode: Mode) -> Self {
+        let vector = match mode {
+            Mode::Heading => (1, 0),
+            Mode::Waypoint => (10, 1),
+        };
+        Ship {
+            mode,
+            path: vec![Step {
+                position: (0, 0),
+                vector,
+            }],
+        }
+    }
+
+    /// Create a ship and execute all actions
+    fn navigate(mode: Mode, actions: &[(Action, i32)]) -> Self {
+        let mut ship = Ship::new(mode);
+        for &(action, value) in actions {
+            ship.execute(action, value);
+        }
+        ship
+    }
+
+    fn current(&self) -> Step {
+        self.path[self.path.len() - 1]
+    }
+
+    fn execute(&mut self, action: Action, value: i32) {
+        let Step {
+            mut position,
+            mut vector,
+        } = self.current();
+        let cardinal = match self.mode {
+            Mode::Heading => &mut position,
+            Mode::Waypoint => &mut vector,
+        };
+        match action {
+            Action::North => cardinal.1 += value,
+            Action::East => cardinal.0 += value,
+            Action::South => cardinal.1 -= value,
+            Action::West => cardinal.0 -= value,
+            Action::Left => vector = rotate_right(vector, -value),
+            Action::Right => vector = rotate_right(vector, value),
             Action::Forward => {
-                let dir = direction.vector();
-                position.0 += dir.0 * value;
-                position.1 += dir.1 * value;
+                position.0 += vector.0 * value;
+                position.1 += vector.1 * value;
             }
         }
+        self.path.push(Step { position, vector });
     }
-    position.0.abs() + position.1.abs()
-}
 
-#[aoc(day12, part2)]
-fn part2(input: &[(Action, i32)]) -> i32 {
-    // (east, north)
-    let mut waypoint_offset = (10, 1);
-    let mut position: (i32, i32) = (0, 0);
-
-    for (action, value) in input {
-        // println!("east: {} north {}", position.0, position.1);
-        // println!("wp east: {} wp north {}", waypoint_offset.0, waypoint_offset.1);
-        match *action {
-            Action::North => waypoint_offset.1 += value,
-            Action::East => waypoint_offset.0 += value,
-            Action::South => waypoint_offset.1 -= value,
-            Action::West => waypoint_offset.0 -= value,
-            Action::Left => {
-                for _ in 0..(value / 90) {
-                    std::mem::swap(&mut waypoint_offset.0, &mut waypoint_offset.1);
-                    waypoint_offset.0 = -waypoint_offset.0;
-                }
-            }
-            Action::Right => {
-                for _ in 0..(value / 90) {
-                    std::mem::swap(&mut waypoint_offset.0, &mut waypoint_offset.1);
-                    waypoint_offset.1 = -waypoint_offset.1;
-                }
-            }
-            Action::Forward => {
-                position.0 += waypoint_offset.0 * value;
-                position.1 += waypoint_offset.1 * value;
+    fn path(&self) -> &[Step] {
+        &self.path
+    }
+
+    /// Smallest and largest coordinates visited by the ship
+    fn bounding_box(&self) -> ((i32, i32), (i32, i32)) {
+        let (xs, ys): (Vec<_>, Vec<_>) = self.path.iter().map(|step| step.position).unzip();
+        let min_max = |values: &[i32]| {
+            let (min, max) = values.iter().minmax().into_option().unwrap();
+            (*min, *max)
+        };
+        let ((min_x, max_x), (min_y, max_y)) = (min_max(&xs), min_max(&ys));
+        ((min_x, min_y), (max_x, max_y))
+    }
+
+    /// Length of all straight moves of the ship
+    fn distance_travelled(&self) -> f64 {
+        self.path
+            .iter()
+            .tuple_windows()
+            .map(|(a, b)| {
+                let dx = f64::from(b.position.0 - a.position.0);
+                let dy = f64::from(b.position.1 - a.position.1);
+                dx.hypot(dy)
+            })
+            .sum()
+    }
+
+    /// Manhattan distance from the start after every action, starting with `0`
+    fn manhattan_distances(&self) -> impl Iterator<Item = i32> + '_ {
+        self.path.iter().map(Step::manhattan_distance)
+    }
+
+    /// Draw the route of the ship, and in waypoint mode also the route of the waypoint
+    fn to_svg(&self) -> String {
+        let positions = self
+            .path
+            .iter()
+            .map(|step| step.position)
+            .dedup()
+            .collect_vec();
+        let waypoints = self.path().iter().map(Step::waypoint).dedup().collect_vec();
+
+        let (mut min, mut max) = self.bounding_box();
+        if self.mode == Mode::Waypoint {
+            for &(x, y) in &waypoints {
+                min = (min.0.min(x), min.1.min(y));
+                max = (max.0.max(x), max.1.max(y));
             }
         }
+
+        let mut svg = Svg::new(min, max, 800);
+        if self.mode == Mode::Waypoint {
+            svg.polyline(&waypoints, "orange", "waypoint");
+        }
+        let farthest = self.manhattan_distances().max().unwrap_or(0);
+        svg.polyline(
+            &positions,
+            "steelblue",
+            &format!(
+                "ship, travelled {:.1}, farthest Manhattan distance {}",
+                self.distance_travelled(),
+                farthest
+            ),
+        );
+        let end = self.current();
+        svg.marker((0, 0), "green", "start (0, 0)");
+        svg.marker(
+            end.position,
+            "red",
+            &format!(
+                "end {:?}, Manhattan distance {}",
+                end.position,
+                end.manhattan_distance()
+            ),
+        );
+        svg.finish()
     }
-    position.0.abs() + position.1.abs()
+}
+
+/// Rotate clockwise by `degrees`, which must be a multiple of 90
+fn rotate_right((east, north): (i32, i32), degrees: i32) -> (i32, i32) {
+    assert_eq!(0, degrees % 90, "Turns must be multiples of 90 degrees");
+    match degrees.rem_euclid(360) / 90 {
+        0 => (east, north),
+        1 => (north, -east),
+        2 => (-east, -north),
+        _ => (-north, east),
+    }
+}
+
+/// Draw the routes of both parts as SVG
+pub fn routes(input: &str) -> Result<Vec<(&'static str, String)>, ParseError> {
+    let input = input_generator(input)?;
+    Ok(vec![
+        ("part1", Ship::navigate(Mode::Heading, &input).to_svg()),
+        ("part2", Ship::navigate(Mode::Waypoint, &input).to_svg()),
+    ])
+}
+
+#[aoc(day12, part1)]
+fn part1(input: &[(Action, i32)]) -> i32 {
+    Ship::navigate(Mode::Heading, input)
+        .current()
+        .manhattan_distance()
+}
+
+#[aoc(day12, part2)]
+fn part2(input: &[(Action, i32)]) -> i32 {
+    Ship::navigate(Mode::Waypoint, input)
+        .current()
+        .manhattan_distance()
+}
+
+#[test]
+fn test_parse_error() {
+    let err = input_generator("F10\nN3\nQ7").unwrap_err();
+    assert_eq!("day12 line 3 column 1: unknown action 'Q'", err.to_string());
+    let err = input_generator("F10\nNx").unwrap_err();
+    assert_eq!((2, 2), (err.line, err.column));
+    let err = input_generator("F10\nL45").unwrap_err();
+    assert_eq!(
+        "day12 line 2 column 2: turns must be multiples of 90 degrees '45'",
+        err.to_string()
+    );
 }
 
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(25, part1(&values));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day12.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day12.txt").trim()).unwrap();
     assert_eq!(1565, part1(&values));
 }
 
 #[test]
 fn test_part2() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(286, part2(&values));
 }
 
 #[test]
 fn test_part2_solution() {
-    let values = input_generator(include_str!("../input/2020/day12.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day12.txt").trim()).unwrap();
     assert_eq!(78883, part2(&values));
 }
 
+#[test]
+fn test_rotate() {
+    assert_eq!((1, -10), rotate_right((10, 1), 90));
+    assert_eq!((-1, 10), rotate_right((10, 1), -90));
+    assert_eq!((-1, 10), rotate_right((10, 1), 270));
+    assert_eq!((-10, -1), rotate_right((10, 1), 180));
+    assert_eq!((10, 1), rotate_right((10, 1), 720));
+    // Rotating and rotating back is a no-op
+    assert_eq!((3, -7), rotate_right(rotate_right((3, -7), -450), 450));
+}
+
+#[test]
+fn test_path() {
+    let values = input_generator(PUZZLE).unwrap();
+    let ship = Ship::navigate(Mode::Heading, &values);
+    assert_eq!(
+        vec![(0, 0), (10, 0), (10, 3), (17, 3), (17, 3), (17, -8)],
+        ship.path().iter().map(|step| step.position).collect_vec()
+    );
+    assert_eq!(
+        vec![0, 10, 13, 20, 20, 25],
+        ship.manhattan_distances().collect_vec()
+    );
+    assert_eq!(((0, -8), (17, 3)), ship.bounding_box());
+    assert_eq!(31., ship.distance_travelled());
+
+    let ship = Ship::navigate(Mode::Waypoint, &values);
+    assert_eq!(
+        vec![
+            (0, 0),
+            (100, 10),
+            (100, 10),
+            (170, 38),
+            (170, 38),
+            (214, -72)
+        ],
+        ship.path().iter().map(|step| step.position).collect_vec()
+    );
+    assert_eq!(
+        vec![
+            (10, 1),
+            (110, 11),
+            (110, 14),
+            (180, 42),
+            (174, 28),
+            (218, -82)
+        ],
+        ship.path().iter().map(Step::waypoint).collect_vec()
+    );
+    assert_eq!(((0, -72), (214, 38)), ship.bounding_box());
+}
+
+#[test]
+fn test_routes() {
+    let routes = routes(PUZZLE).unwrap();
+    assert_eq!(
+        vec!["part1", "part2"],
+        routes.iter().map(|(name, _)| *name).collect_vec()
+    );
+    let part1 = &routes[0].1;
+    assert!(part1.contains(r#"points="0,0 10,0 10,-3 17,-3 17,8""#));
+    assert!(!part1.contains("waypoint"));
+    assert!(part1.contains("<title>end (17, -8), Manhattan distance 25</title>"));
+    assert!(part1.contains("<title>ship, travelled 31.0, farthest Manhattan distance 25</title>"));
+
+    let part2 = &routes[1].1;
+    assert!(part2.contains(r#"points="10,-1 110,-11 110,-14 180,-42 174,-28 218,82""#));
+    assert!(part2.contains(r#"points="0,0 100,-10 170,-38 214,72""#));
+    assert!(part2.contains("<title>end (214, -72), Manhattan distance 286</title>"));
+    assert!(part2.contains("<title>ship, travelled 294.4, farthest Manhattan distance 286</title>"));
+}
+
 #[cfg(test)]
 static PUZZLE: &str = r#"F10
 N3
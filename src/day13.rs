@@ -1,25 +1,30 @@
+use crate::modular::{crt, CrtError};
+use crate::prelude::*;
+
 #[aoc_generator(day13)]
-fn input_generator(input: &str) -> (u32, Vec<Option<u32>>) {
+fn input_generator(input: &str) -> Result<(u32, Vec<Option<u32>>), ParseError> {
+    let ctx = ParseContext::new(13, input);
     let mut iter = input.split('\n');
-    let earliest = iter.next().unwrap().parse().unwrap();
-    let ids = iter
-        .next()
-        .unwrap()
+    let earliest = ctx.parse(iter.next().unwrap_or_default())?;
+    let ids = ctx
+        .require(iter.next(), input, "line with bus ids")?
         .split(',')
         .map(|id| {
             if id == "x" {
-                None
-            } else {
-                Some(id.parse().unwrap())
+                return Ok(None);
+            }
+            match ctx.parse(id)? {
+                0 => Err(ctx.error(id, "bus ids must be positive")),
+                id => Ok(Some(id)),
             }
         })
-        .collect();
-    (earliest, ids)
+        .collect::<Result<_, _>>()?;
+    Ok((earliest, ids))
 }
 
 #[aoc(day13, part1)]
 fn part1((earliest, ids): &(u32, Vec<Option<u32>>)) -> u32 {
-    let mut earliest_time = u32::max_value();
+    let mut earliest_time = u32::MAX;
     let mut earliest_id = 0;
 
     for &id in ids {
@@ -36,45 +41,69 @@ fn part1((earliest, ids): &(u32, Vec<Option<u32>>)) -> u32 {
 }
 
 #[aoc(day13, part2)]
-fn part2((_, ids): &(u32, Vec<Option<u32>>)) -> u64 {
-    let mut time: u64 = 1000;
-    // The increment encapsulates all the previously seen ids
-    // If the increment is a multiple of the ID, then adding the increment will never destroy the time alignment of those IDs.
-    let mut increment = 1;
-
-    for (offset, id) in ids.iter().enumerate() {
-        if let Some(id) = *id {
-            while (time + (offset as u64)) % u64::from(id) != 0 {
-                time += increment;
-            }
-            increment *= u64::from(id);
-        }
-    }
-    time
+fn part2((_, ids): &(u32, Vec<Option<u32>>)) -> Result<u128, CrtError> {
+    // Bus `id` departs `offset` minutes after the timestamp, i.e., `time ≡ -offset (mod id)`
+    let congruences = ids
+        .iter()
+        .enumerate()
+        .filter_map(|(offset, id)| {
+            let id = u128::from((*id)?);
+            Some(((id - offset as u128 % id) % id, id))
+        })
+        .collect_vec();
+    Ok(crt(&congruences)?.0)
 }
 
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(295, part1(&values));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day13.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day13.txt").trim()).unwrap();
     assert_eq!(119, part1(&values));
 }
 
 #[test]
 fn test_part2() {
-    let values = input_generator(PUZZLE);
-    assert_eq!(1068781, part2(&values));
+    let values = input_generator(PUZZLE).unwrap();
+    assert_eq!(1068781, part2(&values).unwrap());
 }
 
 #[test]
 fn test_part2_solution() {
-    let values = input_generator(include_str!("../input/2020/day13.txt").trim());
-    assert_eq!(1106724616194525, part2(&values));
+    let values = input_generator(include_str!("../input/2020/day13.txt").trim()).unwrap();
+    assert_eq!(1106724616194525, part2(&values).unwrap());
+}
+
+#[test]
+fn test_part2_small() {
+    // Timestamps below the old starting point of the search
+    let values = input_generator("0\n2,3").unwrap();
+    assert_eq!(Ok(2), part2(&values));
+    let values = input_generator("0\n17,x,13,19").unwrap();
+    assert_eq!(Ok(3417), part2(&values));
+}
+
+#[test]
+fn test_part2_not_coprime() {
+    // time ≡ 0 (mod 4) and time ≡ 4 (mod 6)
+    let values = input_generator("0\n4,x,6").unwrap();
+    assert_eq!(Ok(4), part2(&values));
+    // time must be even for 4 and odd for 6
+    let values = input_generator("0\n4,6").unwrap();
+    assert_eq!(Err(CrtError::NoSolution { index: 1 }), part2(&values));
+}
+
+#[test]
+fn test_zero_bus_id() {
+    for id in &["0", "00", "+0"] {
+        let err = input_generator(&format!("939\n7,x,{}", id)).unwrap_err();
+        assert_eq!((2, 5), (err.line, err.column));
+        assert_eq!("bus ids must be positive", err.reason);
+    }
 }
 
 #[cfg(test)]
@@ -1,130 +1,273 @@
 use crate::prelude::*;
-use std::convert::TryInto;
+
+/// Number of bits of each address and value
+const BITS: u32 = 36;
+const ALL_BITS: u64 = (1 << BITS) - 1;
+
+/// The bitmask of a `mask = ...` line, split into one integer mask per symbol
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct Mask {
+    /// Bits which are not overwritten with `0`
+    and: u64,
+    /// Bits which are overwritten with `1`
+    or: u64,
+    /// Bits marked with `X`
+    float: u64,
+}
+
+impl Mask {
+    /// Parse the 36 mask chars, returning the index of the first invalid char on error
+    fn from_chars(mask: &str) -> Result<Self, usize> {
+        let mut res = Mask {
+            and: 0,
+            or: 0,
+            float: 0,
+        };
+        for (idx, c) in mask.char_indices() {
+            res.and <<= 1;
+            res.or <<= 1;
+            res.float <<= 1;
+            match c {
+                'X' => {
+                    res.and |= 1;
+                    res.float |= 1;
+                }
+                '1' => {
+                    res.and |= 1;
+                    res.or |= 1;
+                }
+                '0' => {}
+                _ => return Err(idx),
+            }
+        }
+        Ok(res)
+    }
+
+    /// Apply the mask to a value, as done in part 1
+    fn apply(&self, value: u64) -> u64 {
+        value & self.and | self.or
+    }
+
+    /// All addresses written to by the mask, as done in part 2
+    fn decode(&self, addr: u64) -> Region {
+        Region {
+            fixed: (addr | self.or) & !self.float & ALL_BITS,
+            float: self.float,
+        }
+    }
+}
+
+impl Default for Mask {
+    fn default() -> Self {
+        Mask {
+            and: ALL_BITS,
+            or: 0,
+            float: 0,
+        }
+    }
+}
+
+/// A set of addresses, which agree with `fixed` on all bits except the `float` bits
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct Region {
+    /// Value of all non-floating bits, all floating bits are `0`
+    fixed: u64,
+    float: u64,
+}
+
+impl Region {
+    fn len(&self) -> u64 {
+        1 << self.float.count_ones()
+    }
+
+    fn intersects(&self, other: &Region) -> bool {
+        (self.fixed ^ other.fixed) & !self.float & !other.float == 0
+    }
+
+    /// Remove all addresses of `other`, returning the remaining disjoint regions
+    ///
+    /// Every floating bit of `self`, which is fixed in `other`, splits off the half not matching `other`.
+    fn subtract(mut self, other: &Region) -> Vec<Region> {
+        if !self.intersects(other) {
+            return vec![self];
+        }
+        let mut out = Vec::new();
+        let mut split = self.float & !other.float;
+        while split != 0 {
+            let bit = split & split.wrapping_neg();
+            split &= !bit;
+            self.float &= !bit;
+            out.push(Region {
+                fixed: self.fixed | (!other.fixed & bit),
+                float: self.float,
+            });
+            self.fixed |= other.fixed & bit;
+        }
+        // The remainder lies completely inside of `other`
+        out
+    }
+}
 
 enum Instruction {
-    Mask([char; 36]),
-    Store { addr: usize, value: usize },
+    Mask(Mask),
+    Store { addr: u64, value: u64 },
 }
 
 #[aoc_generator(day14)]
-fn input_generator(input: &str) -> Vec<Instruction> {
+fn input_generator(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    let ctx = ParseContext::new(14, input);
     input
         .split('\n')
         .map(|line| {
-            if line.starts_with("mask") {
-                Instruction::Mask(
-                    line.split(" = ")
-                        .nth(1)
-                        .unwrap()
-                        .chars()
-                        .collect_vec()
-                        .try_into()
-                        .unwrap(),
-                )
+            if let Some(mask) = line.strip_prefix("mask = ") {
+                let parsed = Mask::from_chars(mask).map_err(|idx| {
+                    let len = mask[idx..].chars().next().map_or(0, char::len_utf8);
+                    ctx.error(&mask[idx..idx + len], "unknown mask char")
+                })?;
+                if mask.len() != BITS as usize {
+                    return Err(ctx.error(mask, "mask must have 36 bits"));
+                }
+                Ok(Instruction::Mask(parsed))
             } else {
-                let mut iter = line.split("] = ");
-                let addr = (&iter.next().unwrap()[4..]).parse().unwrap();
-                let value = iter.next().unwrap().parse().unwrap();
-                Instruction::Store { addr, value }
+                let store = ctx.require(line.strip_prefix("mem["), line, "`mask` or `mem[`")?;
+                let mut iter = store.split("] = ");
+                let addr = ctx.parse(iter.next().unwrap_or_default())?;
+                let value = ctx.parse(ctx.require(iter.next(), line, "`] = `")?)?;
+                Ok(Instruction::Store { addr, value })
             }
         })
         .collect()
 }
 
 #[aoc(day14, part1)]
-fn part1(input: &[Instruction]) -> usize {
-    let mut mask = ['X'; 36];
-    let mut memory = Map::<usize, usize>::new();
+fn part1(input: &[Instruction]) -> u64 {
+    let mut mask = Mask::default();
+    let mut memory = Map::<u64, u64>::new();
     for inst in input {
         match inst {
             Instruction::Mask(m) => {
                 mask = *m;
             }
             Instruction::Store { addr, value } => {
-                let value_bin = format!("{:0>36b}", value);
-                let value = usize::from_str_radix(
-                    &value_bin
-                        .chars()
-                        .zip(mask.iter().cloned())
-                        .map(|(v, m)| match m {
-                            'X' => v,
-                            '1' => '1',
-                            '0' => '0',
-                            _ => panic!("Unknown mask char: {}", m),
-                        })
-                        .collect::<String>(),
-                    2,
-                )
-                .unwrap();
-                memory.insert(*addr, value);
+                memory.insert(*addr, mask.apply(*value));
             }
         }
     }
     memory.values().sum()
 }
 
+/// Solve part 2 without enumerating all floating addresses
+///
+/// Memory is tracked as a list of disjoint regions with a value each.
+/// Every store first removes its region from all previous regions, such that later writes overwrite earlier ones.
 #[aoc(day14, part2)]
-fn part2(input: &[Instruction]) -> usize {
-    let mut mask = ['X'; 36];
-    let mut memory = Map::<usize, usize>::new();
+fn part2(input: &[Instruction]) -> u128 {
+    let mut mask = Mask::default();
+    let mut memory: Vec<(Region, u64)> = Vec::new();
     for inst in input {
         match inst {
             Instruction::Mask(m) => {
                 mask = *m;
             }
             Instruction::Store { addr, value } => {
-                // Count floating bits
-                let floating_bits = mask.iter().filter(|&&c| c == 'X').count();
-                for num in 0..2usize.pow(floating_bits as u32) {
-                    let floating_addr_bits = format!("{:0>width$b}", num, width = floating_bits);
-                    let mut floating_addr_bits = floating_addr_bits.chars();
-                    let addr = format!("{:0>36b}", addr);
-                    let addr = usize::from_str_radix(
-                        &addr
-                            .chars()
-                            .zip(mask.iter().cloned())
-                            .map(|(a, m)| match m {
-                                'X' => floating_addr_bits.next().unwrap(),
-                                '1' => '1',
-                                '0' => a,
-                                _ => panic!("Unknown mask char: {}", m),
-                            })
-                            .collect::<String>(),
-                        2,
-                    )
-                    .unwrap();
-                    memory.insert(addr, *value);
-                }
+                let region = mask.decode(*addr);
+                memory = memory
+                    .into_iter()
+                    .flat_map(|(old, old_value)| {
+                        old.subtract(&region)
+                            .into_iter()
+                            .map(move |piece| (piece, old_value))
+                    })
+                    .collect();
+                memory.push((region, *value));
             }
         }
     }
-    memory.values().sum()
+    memory
+        .iter()
+        // A region has up to 2^36 addresses, so the sum needs more than 64 bit
+        .map(|(region, value)| u128::from(region.len()) * u128::from(*value))
+        .sum()
 }
 
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(165, part1(&values));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day14.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day14.txt").trim()).unwrap();
     assert_eq!(7817357407588, part1(&values));
 }
 
 #[test]
 fn test_part2() {
-    let values = input_generator(PUZZLE_PART2);
+    let values = input_generator(PUZZLE_PART2).unwrap();
     assert_eq!(208, part2(&values));
 }
 
 #[test]
 fn test_part2_solution() {
-    let values = input_generator(include_str!("../input/2020/day14.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day14.txt").trim()).unwrap();
     assert_eq!(4335927555692, part2(&values));
 }
 
+#[test]
+fn test_mask() {
+    let mask = Mask::from_chars("XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X").unwrap();
+    assert_eq!(73, mask.apply(11));
+    assert_eq!(101, mask.apply(101));
+    assert_eq!(64, mask.apply(0));
+    assert_eq!(Err(2), Mask::from_chars("10Y"));
+}
+
+#[test]
+fn test_region_subtract() {
+    let region = |fixed, float| Region { fixed, float };
+    // Disjoint regions are kept as is
+    assert_eq!(
+        vec![region(0, 0b10)],
+        region(0, 0b10).subtract(&region(1, 0))
+    );
+    // Regions inside of `other` vanish
+    assert!(region(0b100, 0b11).subtract(&region(0, 0b111)).is_empty());
+    // 0b0XX without 0b01X leaves 0b00X
+    assert_eq!(
+        vec![region(0b000, 0b01)],
+        region(0, 0b11).subtract(&region(0b010, 0b01))
+    );
+    // 0bXXX without 0b101 leaves 0bXX0, 0bX11, 0b001
+    let rest = region(0, 0b111).subtract(&region(0b101, 0));
+    assert_eq!(
+        vec![region(0b000, 0b110), region(0b011, 0b100), region(0b001, 0)],
+        rest
+    );
+    assert_eq!(7, rest.iter().map(Region::len).sum::<u64>());
+}
+
+#[test]
+fn test_part2_all_floating() {
+    // 2^36 addresses, partially overwritten by 2^35 addresses
+    let values = input_generator(
+        "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX
+mem[0] = 3
+mask = 1XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX
+mem[0] = 1",
+    )
+    .unwrap();
+    assert_eq!(3 * (1 << 35) + (1 << 35), part2(&values));
+
+    // The sum does not fit into 64 bit
+    let values = input_generator(
+        "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX
+mem[0] = 68719476735",
+    )
+    .unwrap();
+    assert_eq!((1 << 36) * ((1 << 36) - 1), part2(&values));
+}
+
 #[cfg(test)]
 static PUZZLE: &str = r#"mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
 mem[8] = 11
@@ -1,11 +1,11 @@
 use crate::prelude::*;
+use std::fmt::{self, Display};
+use std::time::{Duration, Instant};
 
 #[aoc_generator(day15)]
-fn input_generator(input: &str) -> Vec<u32> {
-    input
-        .split(',')
-        .map(|value| value.parse().unwrap())
-        .collect()
+fn input_generator(input: &str) -> Result<Vec<u32>, ParseError> {
+    let ctx = ParseContext::new(15, input);
+    input.split(',').map(|value| ctx.parse(value)).collect()
 }
 
 #[aoc(day15, part1)]
@@ -15,33 +15,147 @@ fn part1(input: &[u32]) -> u32 {
 
 #[aoc(day15, part2)]
 fn part2(input: &[u32]) -> u32 {
-    numbers(&input).nth(30000000 - 1).unwrap()
+    numbers(input).nth(30000000 - 1).unwrap()
 }
 
-fn numbers<'a>(input: &'a [u32]) -> impl Iterator<Item = u32> + 'a {
-    let mut last_seen = Map::<u32, u32>::new();
-    let mut last_value = input[0];
-    let mut input = input.iter().cloned().fuse();
-    let mut round = 0;
-    std::iter::from_fn(move || {
-        let value = if let Some(value) = input.next() {
-            value
+/// Same as part 2, but also report how many turns per second were played
+#[aoc(day15, part2, throughput)]
+fn part2_throughput(input: &[u32]) -> Timed {
+    value_at_timed(input, 30000000).unwrap()
+}
+
+fn numbers(input: &[u32]) -> VanEck {
+    VanEck::new(input)
+}
+
+/// Values below this are tracked in a flat array, larger ones in a hash map
+///
+/// Every value is either a starting number or an age, so it is smaller than the number of turns played.
+/// Large values are rare, so the array does not need to cover all of them.
+/// The array only grows up to this limit as larger values are spoken, so short games stay cheap.
+const FLAT_LIMIT: usize = 1 << 22;
+
+/// Iterator over the Van Eck sequence following the starting numbers
+struct VanEck {
+    start: Vec<u32>,
+    /// Number of values produced so far
+    turn: u32,
+    last: u32,
+    /// Turn (1-based) in which a value was last spoken, `0` if never
+    flat: Vec<u32>,
+    flat_limit: usize,
+    sparse: HashMap<u32, u32>,
+}
+
+impl VanEck {
+    fn new(start: &[u32]) -> Self {
+        Self::with_flat_limit(start, FLAT_LIMIT)
+    }
+
+    /// Only track values below `limit` in the flat array
+    fn with_flat_limit(start: &[u32], limit: usize) -> Self {
+        VanEck {
+            start: start.to_vec(),
+            turn: 0,
+            last: 0,
+            flat: Vec::new(),
+            flat_limit: limit,
+            sparse: HashMap::new(),
+        }
+    }
+
+    /// Store that `value` was spoken in `turn` and return the previous turn, or `0`
+    fn record(&mut self, value: u32, turn: u32) -> u32 {
+        match self.flat.get_mut(value as usize) {
+            Some(prev) => std::mem::replace(prev, turn),
+            None => self.record_outside(value, turn),
+        }
+    }
+
+    /// Grow the flat array to cover `value` if it is below the limit, otherwise use the hash map
+    #[cold]
+    fn record_outside(&mut self, value: u32, turn: u32) -> u32 {
+        let idx = value as usize;
+        if idx < self.flat_limit {
+            // Grow geometrically, such that resizing takes amortized constant time
+            let len = (idx + 1).max(2 * self.flat.len()).min(self.flat_limit);
+            self.flat.resize(len, 0);
+            std::mem::replace(&mut self.flat[idx], turn)
+        } else {
+            self.sparse.insert(value, turn).unwrap_or(0)
+        }
+    }
+}
+
+impl Iterator for VanEck {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        let turn = self.turn;
+        let value = if turn == 0 {
+            *self.start.first()?
         } else {
-            match last_seen.get(&last_value) {
-                None => 0,
-                Some(&last_round) => round - last_round,
+            let prev = self.record(self.last, turn);
+            match self.start.get(turn as usize) {
+                Some(&value) => value,
+                None if prev == 0 => 0,
+                None => turn - prev,
             }
         };
-        last_seen.insert(last_value, round);
-        last_value = value;
-        round += 1;
+        self.turn = turn.checked_add(1)?;
+        self.last = value;
         Some(value)
+    }
+}
+
+/// Value spoken in `turn` (1-based) for any starting sequence
+///
+/// Returns `None` for an empty starting sequence or turn `0`.
+fn value_at(start: &[u32], turn: usize) -> Option<u32> {
+    VanEck::new(start).nth(turn.checked_sub(1)?)
+}
+
+/// A result together with the number of turns played per second
+#[derive(Copy, Clone, Debug)]
+struct Timed {
+    value: u32,
+    turns: usize,
+    elapsed: Duration,
+}
+
+impl Timed {
+    fn turns_per_second(&self) -> f64 {
+        self.turns as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+impl Display for Timed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({} turns in {:.3?}, {:.1} M turns/s)",
+            self.value,
+            self.turns,
+            self.elapsed,
+            self.turns_per_second() / 1e6
+        )
+    }
+}
+
+/// Like [`value_at`], but measures the throughput
+fn value_at_timed(start: &[u32], turn: usize) -> Option<Timed> {
+    let start_time = Instant::now();
+    let value = value_at(start, turn)?;
+    Some(Timed {
+        value,
+        turns: turn,
+        elapsed: start_time.elapsed(),
     })
 }
 
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(
         vec![0, 3, 6, 0, 3, 3, 1, 0, 4, 0],
         numbers(&values).take(10).collect::<Vec<_>>()
@@ -50,57 +164,95 @@ fn test_part1() {
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day15.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day15.txt").trim()).unwrap();
     assert_eq!(1522, part1(&values));
 }
 
 #[test]
 fn test_part2() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(175594, numbers(&values).nth(30000000 - 1).unwrap());
 }
 
 #[test]
 fn test_part2_1_3_2() {
-    let values = input_generator("1,3,2");
+    let values = input_generator("1,3,2").unwrap();
     assert_eq!(2578, numbers(&values).nth(30000000 - 1).unwrap());
 }
 
 #[test]
 fn test_part2_2_1_3() {
-    let values = input_generator("2,1,3");
+    let values = input_generator("2,1,3").unwrap();
     assert_eq!(3544142, numbers(&values).nth(30000000 - 1).unwrap());
 }
 
 #[test]
 fn test_part2_1_2_3() {
-    let values = input_generator("1,2,3");
+    let values = input_generator("1,2,3").unwrap();
     assert_eq!(261214, numbers(&values).nth(30000000 - 1).unwrap());
 }
 
 #[test]
 fn test_part2_2_3_1() {
-    let values = input_generator("2,3,1");
+    let values = input_generator("2,3,1").unwrap();
     assert_eq!(6895259, numbers(&values).nth(30000000 - 1).unwrap());
 }
 
 #[test]
 fn test_part2_3_2_1() {
-    let values = input_generator("3,2,1");
+    let values = input_generator("3,2,1").unwrap();
     assert_eq!(18, numbers(&values).nth(30000000 - 1).unwrap());
 }
 
 #[test]
 fn test_part2_3_1_2() {
-    let values = input_generator("3,1,2");
+    let values = input_generator("3,1,2").unwrap();
     assert_eq!(362, numbers(&values).nth(30000000 - 1).unwrap());
 }
 
 #[test]
 fn test_part2_solution() {
-    let values = input_generator(include_str!("../input/2020/day15.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day15.txt").trim()).unwrap();
     assert_eq!(18234, part2(&values));
 }
 
+#[test]
+fn test_value_at() {
+    assert_eq!(Some(436), value_at(&[0, 3, 6], 2020));
+    assert_eq!(Some(6), value_at(&[0, 3, 6], 3));
+    assert_eq!(Some(0), value_at(&[0, 3, 6], 4));
+    assert_eq!(Some(1836), value_at(&[3, 1, 2], 2020));
+    // Starting numbers larger than the number of turns
+    assert_eq!(Some(0), value_at(&[100, 200], 3));
+    assert_eq!(None, value_at(&[], 10));
+    assert_eq!(None, value_at(&[1], 0));
+
+    let timed = value_at_timed(&[0, 3, 6], 2020).unwrap();
+    assert_eq!(436, timed.value);
+    assert!(timed.to_string().starts_with("436 (2020 turns in "));
+}
+
+#[test]
+fn test_flat_limit() {
+    // The hash fallback gives the same sequence as the flat array
+    let flat = VanEck::new(&[0, 3, 6]).take(100_000);
+    let sparse = VanEck::with_flat_limit(&[0, 3, 6], 10).take(100_000);
+    assert!(flat.eq(sparse));
+    let no_flat = VanEck::with_flat_limit(&[0, 3, 6], 0).take(100_000);
+    assert!(VanEck::new(&[0, 3, 6]).take(100_000).eq(no_flat));
+}
+
+#[test]
+fn test_flat_growth() {
+    // Short games only allocate what the spoken values need
+    let mut numbers = VanEck::new(&[0, 3, 6]);
+    assert_eq!(Some(436), numbers.nth(2020 - 1));
+    assert!(numbers.flat.len() <= 2 * 2020);
+    let mut numbers = VanEck::new(&[5_000_000]);
+    assert_eq!(Some(0), numbers.nth(2));
+    assert_eq!(1, numbers.flat.len());
+    assert_eq!(1, numbers.sparse.len());
+}
+
 #[cfg(test)]
 static PUZZLE: &str = r#"0,3,6"#;
@@ -1,3 +1,4 @@
+use crate::assignment::{assign, AssignmentError};
 use crate::prelude::*;
 use std::ops::RangeInclusive;
 
@@ -20,15 +21,23 @@ struct Input {
 }
 
 #[aoc_generator(day16)]
-fn input_generator(input: &str) -> Input {
+fn input_generator(input: &str) -> Result<Input, ParseError> {
+    let ctx = ParseContext::new(16, input);
+    let parse_ticket = |line: &str| {
+        line.split(',')
+            .map(|x| ctx.parse(x))
+            .collect::<Result<Vec<u32>, _>>()
+    };
     let mut lines = input.lines();
 
     let mut categories = Map::new();
     for line in &mut lines {
-        if line == "" {
+        if line.is_empty() {
             break;
         }
-        let category: Category = line.parse().unwrap();
+        let category: Category = line
+            .parse()
+            .map_err(|_| ctx.error(line, "expected `<name>: <min>-<max> or <min>-<max>`, got"))?;
         categories.insert(
             category.name,
             (
@@ -40,26 +49,19 @@ fn input_generator(input: &str) -> Input {
 
     // your ticket:
     lines.next();
-    let own_ticket = lines
-        .next()
-        .unwrap()
-        .split(',')
-        .map(|x| x.parse().unwrap())
-        .collect();
+    let own_ticket = parse_ticket(ctx.require(lines.next(), input, "own ticket")?)?;
     // empty line
     lines.next();
     // nearby tickets:
     lines.next();
 
-    let nearby_tickets = lines
-        .map(|line| line.split(',').map(|x| x.parse().unwrap()).collect())
-        .collect();
+    let nearby_tickets = lines.map(parse_ticket).collect::<Result<_, _>>()?;
 
-    Input {
+    Ok(Input {
         categories,
         own_ticket,
         nearby_tickets,
-    }
+    })
 }
 
 #[aoc(day16, part1)]
@@ -69,7 +71,7 @@ fn part1(input: &Input) -> u32 {
         'value: for value in nearby_ticket {
             for (range1, range2) in input.categories.values() {
                 // Check if value is valid in any range
-                if range1.contains(&value) || range2.contains(&value) {
+                if range1.contains(value) || range2.contains(value) {
                     continue 'value;
                 }
             }
@@ -80,17 +82,16 @@ fn part1(input: &Input) -> u32 {
 }
 
 #[aoc(day16, part2)]
-fn part2(input: &Input) -> u64 {
+fn part2(input: &Input) -> Result<u64, AssignmentError<String, usize>> {
     // Filter out invalid tickets
     let nearby_tickets = input
         .nearby_tickets
         .iter()
-        .cloned()
         .filter(|nearby_ticket| {
-            'value: for value in nearby_ticket {
+            'value: for value in nearby_ticket.iter() {
                 for (range1, range2) in input.categories.values() {
                     // Check if value is valid in any range
-                    if range1.contains(&value) || range2.contains(&value) {
+                    if range1.contains(value) || range2.contains(value) {
                         continue 'value;
                     }
                 }
@@ -98,104 +99,55 @@ fn part2(input: &Input) -> u64 {
             }
             true
         })
+        .cloned()
         .collect_vec();
 
-    let mut assigned_columns = Set::new();
-    let mut category_mapping = Map::new();
-    for cat in &input.categories {
-        category_mapping.insert(cat.0, (0..input.categories.len()).collect::<Set<_>>());
-    }
-
-    // Mark all incompatible columns
-    for col in 0..input.categories.len() {
-        // Already assigned this column
-        if assigned_columns.get(&col).is_some() {
-            continue;
-        }
-
-        'category: for (cat_name, (range1, range2)) in &input.categories {
-            if category_mapping[cat_name].len() == 1 || !category_mapping[cat_name].contains(&col) {
-                // Category only has 1 column left, skip
-                continue;
-            }
-
-            // Try to find any mismatches between columns and categories
-            for nearby_ticket in &nearby_tickets {
-                let val = nearby_ticket[col];
-                if !range1.contains(&val) && !range2.contains(&val) {
-                    println!(
-                        "Value {} in Col {} is not compatible with Cat {} ({:?}, {:?})",
-                        val, col, cat_name, range1, range2
-                    );
-                    category_mapping.get_mut(cat_name).unwrap().remove(&col);
-                    continue 'category;
-                }
-            }
-        }
-    }
-
-    while assigned_columns.len() != input.categories.len() {
-        println!("\nAssigned Column: {:?}", assigned_columns);
-        println!("Possible Assignments:");
-        for (cat_name, cols) in &category_mapping {
-            println!("  {}: {:?}", cat_name, cols);
-        }
-
-        // Update set of assigned columns
-        assigned_columns = category_mapping
-            .iter()
-            .filter(|(_, cm)| cm.len() == 1)
-            .map(|(name, cm)| {
-                let col = *cm.iter().next().unwrap();
-                println!("{}: {}", name, col);
-                col
-            })
-            .collect();
-        // Remove all assigned columns from the category mapping
-        category_mapping.values_mut().for_each(|cols| {
-            // Need to ignore all categories which are already assigned, i.e., only have one col left.
-            if cols.len() > 1 {
-                for col in &assigned_columns {
-                    cols.remove(col);
-                }
-            }
-        });
-    }
-
-    println!("\nAssignments:");
-    for (cat_name, cols) in &category_mapping {
-        println!("  {}: {:?}", cat_name, cols);
-    }
-
-    category_mapping
+    // Every category can be in the columns, where all values are within its ranges
+    let candidates: Map<String, Set<usize>> = input
+        .categories
         .iter()
+        .map(|(cat_name, (range1, range2))| {
+            let cols = (0..input.categories.len())
+                .filter(|&col| {
+                    nearby_tickets.iter().all(|nearby_ticket| {
+                        let val = nearby_ticket[col];
+                        range1.contains(&val) || range2.contains(&val)
+                    })
+                })
+                .collect();
+            (cat_name.clone(), cols)
+        })
+        .collect();
+
+    Ok(assign(&candidates)?
+        .into_iter()
         .filter(|(name, _)| name.starts_with("departure"))
-        .map(|(_, cols)| input.own_ticket[*cols.iter().next().unwrap()] as u64)
-        .product()
+        .map(|(_, col)| input.own_ticket[col] as u64)
+        .product())
 }
 
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(71, part1(&values));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day16.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day16.txt").trim()).unwrap();
     assert_eq!(21980, part1(&values));
 }
 
 #[test]
 fn test_part2() {
-    let values = input_generator(PUZZLE_PART2);
-    assert_eq!(1, part2(&values));
+    let values = input_generator(PUZZLE_PART2).unwrap();
+    assert_eq!(1, part2(&values).unwrap());
 }
 
 #[test]
 fn test_part2_solution() {
-    let values = input_generator(include_str!("../input/2020/day16.txt").trim());
-    assert_eq!(1439429522627, part2(&values));
+    let values = input_generator(include_str!("../input/2020/day16.txt").trim()).unwrap();
+    assert_eq!(1439429522627, part2(&values).unwrap());
 }
 
 #[cfg(test)]
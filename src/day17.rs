@@ -1,198 +1,303 @@
+use crate::animation::Animation;
+use crate::grid::Grid;
 use crate::prelude::*;
-use misc_utils::{Max, Min};
 
 #[aoc_generator(day17)]
-fn input_generator(input: &str) -> Set<(i32, i32)> {
-    input
-        .lines()
-        .enumerate()
-        .flat_map(|(line_idx, line)| {
-            line.chars().enumerate().filter_map(move |(char_idx, c)| {
-                if c == '#' {
-                    Some((line_idx as _, char_idx as _))
-                } else {
-                    None
-                }
-            })
-        })
-        .collect()
+fn input_generator(input: &str) -> Result<Set<(i32, i32)>, ParseError> {
+    let slice: Grid<bool> = Grid::parse(&ParseContext::new(17, input), input)?;
+    Ok(slice
+        .positions()
+        .filter(|&pos| slice[pos])
+        .map(|(x, y)| (y as _, x as _))
+        .collect())
 }
 
-#[aoc(day17, part1)]
-fn part1(input: &Set<(i32, i32)>) -> usize {
-    let mut state: Set<(_, _, _)> = input.iter().map(|&(x, y)| (x, y, 0)).collect();
-    let mut new_state = state.clone();
+/// Number of active neighbors which make an inactive cube active (`birth`) or keep an active cube active (`survive`)
+#[derive(Copy, Clone, Debug)]
+struct Rules {
+    birth: &'static [usize],
+    survive: &'static [usize],
+}
 
-    fn neighbors3((xp, yp, zp): (i32, i32, i32)) -> impl Iterator<Item = (i32, i32, i32)> {
-        [-1, 0, 1]
+/// The rules of the Conway Cubes
+static CONWAY_CUBES: Rules = Rules {
+    birth: &[3],
+    survive: &[2, 3],
+};
+
+/// A sparse cellular automaton in `N` dimensions, which only stores the active cubes
+#[derive(Clone, Debug)]
+struct Automaton<const N: usize> {
+    active: HashSet<[i32; N]>,
+    rules: Rules,
+    /// Offsets to all `3^N - 1` neighbors
+    neighbor_offsets: Vec<[i32; N]>,
+}
+
+impl<const N: usize> Automaton<N> {
+    /// Place the 2D slice of the input at the origin of all other dimensions
+    fn new(slice: &Set<(i32, i32)>, rules: Rules) -> Self {
+        assert!(N >= 2, "The input slice needs at least two dimensions");
+        assert!(
+            !rules.birth.contains(&0),
+            "Births without neighbors would activate infinitely many cubes"
+        );
+
+        let active = slice
             .iter()
-            .flat_map(move |&x| {
-                [-1, 0, 1]
-                    .iter()
-                    .flat_map(move |&y| [-1, 0, 1].iter().map(move |&z| (xp + x, yp + y, zp + z)))
+            .map(|&(x, y)| {
+                let mut cube = [0; N];
+                cube[0] = x;
+                cube[1] = y;
+                cube
             })
-            .filter(move |p| p != &(xp, yp, zp))
-    }
+            .collect();
 
-    for _ in 0..6 {
-        // Find min and max values in all dimensions
-        let mut min_x = Min::new();
-        let mut max_x = Max::new();
-        let mut min_y = Min::new();
-        let mut max_y = Max::new();
-        let mut min_z = Min::new();
-        let mut max_z = Max::new();
-
-        for (x, y, z) in &state {
-            min_x.update(*x);
-            max_x.update(*x);
-            min_y.update(*y);
-            max_y.update(*y);
-            min_z.update(*z);
-            max_z.update(*z);
+        let neighbor_offsets = (0..N)
+            .map(|_| -1..=1)
+            .multi_cartesian_product()
+            .filter(|offset| offset.iter().any(|&o| o != 0))
+            .map(|offset| {
+                let mut res = [0; N];
+                res.copy_from_slice(&offset);
+                res
+            })
+            .collect();
+
+        Automaton {
+            active,
+            rules,
+            neighbor_offsets,
         }
+    }
 
-        let min_x = min_x.get_min_extreme() - 1;
-        let min_y = min_y.get_min_extreme() - 1;
-        let min_z = min_z.get_min_extreme() - 1;
-        let max_x = max_x.get_max_extreme() + 1;
-        let max_y = max_y.get_max_extreme() + 1;
-        let max_z = max_z.get_max_extreme() + 1;
-
-        new_state.clone_from(&state);
-
-        for x in min_x..=max_x {
-            for y in min_y..=max_y {
-                for z in min_z..=max_z {
-                    let actives = neighbors3((x, y, z)).filter(|p| state.contains(p)).count();
-
-                    match (state.contains(&(x, y, z)), actives) {
-                        (true, 2) | (true, 3) => {}
-                        (true, _) => {
-                            new_state.remove(&(x, y, z));
-                        }
-                        (false, 3) => {
-                            new_state.insert((x, y, z));
-                        }
-                        _ => {}
-                    }
-                }
+    fn neighbors<'a>(&'a self, cube: &'a [i32; N]) -> impl Iterator<Item = [i32; N]> + 'a {
+        self.neighbor_offsets.iter().map(move |offset| {
+            let mut neighbor = *cube;
+            neighbor
+                .iter_mut()
+                .zip(offset)
+                .for_each(|(coord, delta)| *coord += delta);
+            neighbor
+        })
+    }
+
+    /// Advance the automaton by one cycle
+    fn step(&mut self) {
+        // Every active cube adds one to the count of each of its neighbors,
+        // such that only cubes next to an active cube are ever looked at.
+        let mut neighbor_counts = HashMap::<[i32; N], usize>::new();
+        for cube in &self.active {
+            for neighbor in self.neighbors(cube) {
+                *neighbor_counts.entry(neighbor).or_default() += 1;
             }
         }
 
-        std::mem::swap(&mut state, &mut new_state);
+        let rules = self.rules;
+        let active = &self.active;
+        let mut next: HashSet<[i32; N]> = neighbor_counts
+            .into_iter()
+            .filter(|(cube, count)| {
+                if active.contains(cube) {
+                    rules.survive.contains(count)
+                } else {
+                    rules.birth.contains(count)
+                }
+            })
+            .map(|(cube, _)| cube)
+            .collect();
+        // Isolated cubes never show up in the neighbor counts
+        if rules.survive.contains(&0) {
+            next.extend(active.iter().filter(|cube| {
+                self.neighbors(cube)
+                    .all(|neighbor| !active.contains(&neighbor))
+            }));
+        }
+        self.active = next;
     }
 
-    state.len()
+    fn active_count(&self) -> usize {
+        self.active.len()
+    }
 }
 
-#[aoc(day17, part2)]
-fn part2(input: &Set<(i32, i32)>) -> usize {
-    let mut state: Set<(_, _, _, _)> = input.iter().map(|&(x, y)| (x, y, 0, 0)).collect();
-    let mut new_state = state.clone();
-
-    fn neighbors4(
-        (xp, yp, zp, wp): (i32, i32, i32, i32),
-    ) -> impl Iterator<Item = (i32, i32, i32, i32)> {
-        [-1, 0, 1]
-            .iter()
-            .flat_map(move |&x| {
-                [-1, 0, 1].iter().flat_map(move |&y| {
-                    [-1, 0, 1].iter().flat_map(move |&w| {
-                        [-1, 0, 1]
-                            .iter()
-                            .map(move |&z| (xp + x, yp + y, zp + z, wp + w))
-                    })
-                })
-            })
-            .filter(move |p| p != &(xp, yp, zp, wp))
+/// Draw all 2D slices of every state next to each other, using the same bounds for all states
+///
+/// The slices are ordered by the third coordinate from left to right and by all further coordinates from top to bottom.
+/// Slices are separated by a column or row of spaces.
+fn mosaic<const N: usize>(states: &[HashSet<[i32; N]>]) -> Vec<Grid<char>> {
+    let mut min = [i32::MAX; N];
+    let mut max = [i32::MIN; N];
+    for cube in states.iter().flatten() {
+        for dim in 0..N {
+            min[dim] = min[dim].min(cube[dim]);
+            max[dim] = max[dim].max(cube[dim]);
+        }
     }
+    if states.iter().all(|state| state.is_empty()) {
+        min = [0; N];
+        max = [0; N];
+    }
+    let len = |dim: usize| (max[dim] - min[dim] + 1) as usize;
 
-    for _ in 0..6 {
-        // Find min and max values in all dimensions
-        let mut min_x = Min::new();
-        let mut max_x = Max::new();
-        let mut min_y = Min::new();
-        let mut max_y = Max::new();
-        let mut min_z = Min::new();
-        let mut max_z = Max::new();
-        let mut min_w = Min::new();
-        let mut max_w = Max::new();
-
-        for (x, y, z, w) in &state {
-            min_x.update(*x);
-            max_x.update(*x);
-            min_y.update(*y);
-            max_y.update(*y);
-            min_z.update(*z);
-            max_z.update(*z);
-            min_w.update(*w);
-            max_w.update(*w);
-        }
+    let (slice_width, slice_height) = (len(1), len(0));
+    let columns = if N > 2 { len(2) } else { 1 };
+    let rows: usize = (3..N).map(len).product();
+    let width = columns * (slice_width + 1) - 1;
+    let height = rows * (slice_height + 1) - 1;
 
-        let min_x = min_x.get_min_extreme() - 1;
-        let min_y = min_y.get_min_extreme() - 1;
-        let min_z = min_z.get_min_extreme() - 1;
-        let min_w = min_w.get_min_extreme() - 1;
-        let max_x = max_x.get_max_extreme() + 1;
-        let max_y = max_y.get_max_extreme() + 1;
-        let max_z = max_z.get_max_extreme() + 1;
-        let max_w = max_w.get_max_extreme() + 1;
-
-        new_state.clone_from(&state);
-
-        for x in min_x..=max_x {
-            for y in min_y..=max_y {
-                for z in min_z..=max_z {
-                    for w in min_w..=max_w {
-                        let p = (x, y, z, w);
-                        let actives = neighbors4(p).filter(|p| state.contains(p)).count();
-
-                        match (state.contains(&p), actives) {
-                            (true, 2) | (true, 3) => {}
-                            (true, _) => {
-                                new_state.remove(&p);
-                            }
-                            (false, 3) => {
-                                new_state.insert(p);
-                            }
-                            _ => {}
-                        }
-                    }
+    states
+        .iter()
+        .map(|state| {
+            let mut grid = Grid::from_fn(width, height, |(x, y)| {
+                if x % (slice_width + 1) == slice_width || y % (slice_height + 1) == slice_height {
+                    ' '
+                } else {
+                    '.'
                 }
+            });
+            for cube in state {
+                let offset = |dim: usize| (cube[dim] - min[dim]) as usize;
+                let column = if N > 2 { offset(2) } else { 0 };
+                let row = (3..N).fold(0, |row, dim| row * len(dim) + offset(dim));
+                let x = column * (slice_width + 1) + offset(1);
+                let y = row * (slice_height + 1) + offset(0);
+                grid[(x, y)] = '#';
             }
+            grid
+        })
+        .collect()
+}
+
+/// Record all six cycles of the boot process in 3 and 4 dimensions
+pub fn animations(input: &str) -> Result<Vec<(&'static str, Animation)>, ParseError> {
+    fn record<const N: usize>(input: &Set<(i32, i32)>) -> Animation {
+        let mut automaton = Automaton::<N>::new(input, CONWAY_CUBES);
+        let mut states = vec![automaton.active.clone()];
+        for _ in 0..6 {
+            automaton.step();
+            states.push(automaton.active.clone());
         }
 
-        std::mem::swap(&mut state, &mut new_state);
+        let mut animation = Animation::new(&[
+            ('.', [20, 20, 40]),
+            ('#', [250, 200, 50]),
+            (' ', [90, 90, 90]),
+        ]);
+        for (cycle, frame) in mosaic(&states).iter().enumerate() {
+            animation.push(format!("Cycle {}", cycle), frame);
+        }
+        animation
+    }
+
+    let input = input_generator(input)?;
+    Ok(vec![
+        ("part1", record::<3>(&input)),
+        ("part2", record::<4>(&input)),
+    ])
+}
+
+/// Run the boot process with 6 cycles in `N` dimensions and count the active cubes
+fn boot<const N: usize>(input: &Set<(i32, i32)>) -> usize {
+    let mut automaton = Automaton::<N>::new(input, CONWAY_CUBES);
+    for _ in 0..6 {
+        automaton.step();
     }
+    automaton.active_count()
+}
+
+#[aoc(day17, part1)]
+fn part1(input: &Set<(i32, i32)>) -> usize {
+    boot::<3>(input)
+}
 
-    state.len()
+#[aoc(day17, part2)]
+fn part2(input: &Set<(i32, i32)>) -> usize {
+    boot::<4>(input)
 }
 
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(112, part1(&values));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day17.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day17.txt").trim()).unwrap();
     assert_eq!(267, part1(&values));
 }
 
 #[test]
 fn test_part2() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(848, part2(&values));
 }
 
 #[test]
 fn test_part2_solution() {
-    let values = input_generator(include_str!("../input/2020/day17.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day17.txt").trim()).unwrap();
     assert_eq!(1812, part2(&values));
 }
 
+#[test]
+fn test_higher_dimensions() {
+    let values = input_generator(PUZZLE).unwrap();
+    assert_eq!(5760, boot::<5>(&values));
+    assert_eq!(35936, boot::<6>(&values));
+}
+
+#[test]
+fn test_custom_rules() {
+    // A single cube survives forever if cubes without neighbors survive
+    let single_cube = input_generator("#").unwrap();
+    let mut automaton = Automaton::<3>::new(
+        &single_cube,
+        Rules {
+            birth: &[3],
+            survive: &[0],
+        },
+    );
+    automaton.step();
+    assert_eq!(1, automaton.active_count());
+
+    let mut automaton = Automaton::<3>::new(&single_cube, CONWAY_CUBES);
+    automaton.step();
+    assert_eq!(0, automaton.active_count());
+}
+
+#[test]
+fn test_mosaic() {
+    let input = input_generator(PUZZLE).unwrap();
+    let mut automaton = Automaton::<3>::new(&input, CONWAY_CUBES);
+    let mut states = vec![automaton.active.clone()];
+    automaton.step();
+    states.push(automaton.active.clone());
+    let frames = mosaic(&states);
+    assert_eq!(
+        "... .#. ...\n\
+         ... ..# ...\n\
+         ... ### ...\n\
+         ... ... ...\n",
+        frames[0].to_string()
+    );
+    assert_eq!(
+        "... ... ...\n\
+         #.. #.# #..\n\
+         ..# .## ..#\n\
+         .#. .#. .#.\n",
+        frames[1].to_string()
+    );
+
+    // Slices of the fourth dimension are stacked vertically
+    let mut automaton = Automaton::<4>::new(&input, CONWAY_CUBES);
+    let before = automaton.active.clone();
+    automaton.step();
+    let frames = mosaic(&[before, automaton.active.clone()]);
+    assert_eq!((11, 14), (frames[1].width(), frames[1].height()));
+    assert_eq!(29, frames[1].iter().filter(|&&c| c == '#').count());
+}
+
 #[cfg(test)]
 static PUZZLE: &str = r#".#.
 ..#
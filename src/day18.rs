@@ -1,331 +1,581 @@
+use crate::prelude::*;
+use num_bigint::{BigInt, Sign};
 use std::iter::Peekable;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 enum Expr {
-    Lit(i64),
+    Lit(BigInt),
     Op(char),
     Brackets(Vec<Expr>),
 }
 
-impl Expr {
-    fn simplify_part1(&mut self) -> &mut Self {
+/// Binding power of the binary operators, higher values bind tighter
+///
+/// All binary operators are left-associative.
+/// Unary minus binds tighter than any binary operator.
+#[derive(Copy, Clone, Debug)]
+struct Precedence(&'static [(char, u8)]);
+
+/// Evaluate strictly from left to right
+static PART1: Precedence = Precedence(&[('+', 1), ('-', 1), ('*', 1), ('/', 1)]);
+/// Addition and subtraction before multiplication and division
+static PART2: Precedence = Precedence(&[('+', 2), ('-', 2), ('*', 1), ('/', 1)]);
+/// The usual school math, used to test the parser with another precedence table
+#[cfg(test)]
+static ARITHMETIC: Precedence = Precedence(&[('+', 1), ('-', 1), ('*', 2), ('/', 2)]);
+
+impl Precedence {
+    fn binding_power(&self, op: char) -> Result<u8, EvalError> {
+        self.0
+            .iter()
+            .find(|&&(c, _)| c == op)
+            .map(|&(_, bp)| bp)
+            .ok_or(EvalError::UnknownOperator(op))
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum EvalError {
+    DivisionByZero,
+    UnknownOperator(char),
+    /// A number or bracket was expected, but an operator or the end was found
+    MissingOperand,
+    /// An operator was expected, but a number or bracket was found
+    MissingOperator,
+}
+
+impl std::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Expr::Brackets(exprs) => {
-                let mut exprs = exprs.iter_mut().map(|e| {
-                    e.simplify_part1();
-                    e
-                });
-                let mut accu = exprs.by_ref().next().unwrap().as_val();
-                while exprs.len() != 0 {
-                    let op = exprs.next().unwrap();
-                    let e = exprs.next().unwrap().as_val();
-                    match op {
-                        Expr::Op('+') => accu += e,
-                        Expr::Op('*') => accu *= e,
-                        _ => panic!("Unknown operator {:?}", op),
-                    }
-                }
-                *self = Expr::Lit(accu);
-            }
+            EvalError::DivisionByZero => write!(f, "division by zero"),
+            EvalError::UnknownOperator(op) => write!(f, "operator `{}` has no precedence", op),
+            EvalError::MissingOperand => write!(f, "missing operand"),
+            EvalError::MissingOperator => write!(f, "missing operator"),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+/// Expression tree with the grouping of one [`Precedence`] applied
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Ast {
+    Lit(BigInt),
+    Neg(Box<Ast>),
+    Binary(char, Box<Ast>, Box<Ast>),
+}
 
-            Expr::Lit(_) => {}
-            Expr::Op(_) => {}
+impl Ast {
+    /// Group the operands of `exprs` with a Pratt parser
+    fn parse(exprs: &[Expr], precedence: &Precedence) -> Result<Self, EvalError> {
+        let mut tokens = exprs.iter().peekable();
+        let ast = Self::parse_bp(&mut tokens, precedence, 0)?;
+        match tokens.next() {
+            None => Ok(ast),
+            Some(_) => Err(EvalError::MissingOperator),
         }
-        self
     }
 
-    fn simplify_part2(&mut self) -> &mut Self {
-        match self {
-            Expr::Brackets(exprs) => {
-                exprs.iter_mut().for_each(|e| {
-                    e.simplify_part2();
-                });
-
-                // First all the sums
-                while exprs.contains(&Expr::Op('+')) {
-                    let mut i = 0;
-                    while exprs.len() > 2 && i < exprs.len() - 2 {
-                        if let [Expr::Lit(a), Expr::Op('+'), Expr::Lit(b)] = &mut exprs[i..][..3] {
-                            *a += *b;
-                            exprs.remove(i + 2);
-                            exprs.remove(i + 1);
-                        };
-                        i += 1;
-                    }
-                }
+    /// Parse an expression containing only operators which bind at least as tight as `min_bp`
+    fn parse_bp<'a>(
+        tokens: &mut Peekable<impl Iterator<Item = &'a Expr>>,
+        precedence: &Precedence,
+        min_bp: u8,
+    ) -> Result<Self, EvalError> {
+        let mut lhs = match tokens.next() {
+            Some(Expr::Lit(val)) => Ast::Lit(val.clone()),
+            Some(Expr::Brackets(exprs)) => Ast::parse(exprs, precedence)?,
+            Some(Expr::Op('-')) => Ast::Neg(Box::new(Self::parse_bp(tokens, precedence, u8::MAX)?)),
+            Some(Expr::Op(_)) | None => return Err(EvalError::MissingOperand),
+        };
 
-                // Multiply everything together
-                let mut exprs = exprs.iter_mut();
-                let mut accu = exprs.by_ref().next().unwrap().as_val();
-                while exprs.len() != 0 {
-                    // Consume op
-                    exprs.next();
-                    let e = exprs.next().unwrap().as_val();
-                    accu *= e;
-                }
-                *self = Expr::Lit(accu);
+        while let Some(token) = tokens.peek() {
+            let op = match token {
+                Expr::Op(op) => *op,
+                _ => return Err(EvalError::MissingOperator),
+            };
+            let bp = precedence.binding_power(op)?;
+            if bp < min_bp {
+                break;
             }
+            tokens.next();
+            let rhs = Self::parse_bp(tokens, precedence, bp + 1)?;
+            lhs = Ast::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
 
-            Expr::Lit(_) => {}
-            Expr::Op(_) => {}
+    fn eval(&self) -> Result<BigInt, EvalError> {
+        match self {
+            Ast::Lit(val) => Ok(val.clone()),
+            Ast::Neg(ast) => Ok(-ast.eval()?),
+            Ast::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval()?, rhs.eval()?);
+                match op {
+                    '+' => Ok(lhs + rhs),
+                    '-' => Ok(lhs - rhs),
+                    '*' => Ok(lhs * rhs),
+                    // Integer division rounds towards zero
+                    '/' if rhs.sign() == Sign::NoSign => Err(EvalError::DivisionByZero),
+                    '/' => Ok(lhs / rhs),
+                    _ => Err(EvalError::UnknownOperator(*op)),
+                }
+            }
         }
-        self
     }
+}
 
-    fn as_val(&self) -> i64 {
+/// Print the fully parenthesized expression, or an S-expression with the alternate flag (`{:#}`)
+///
+/// The printed expression evaluates to the same value independent of the precedence table used for parsing it.
+impl std::fmt::Display for Ast {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Expr::Lit(val) => *val,
-            _ => panic!("Cannot convert to value"),
+            Ast::Lit(val) if f.alternate() || val.sign() != Sign::Minus => write!(f, "{}", val),
+            // Negative literals only occur in hand-built trees and are printed as negation
+            Ast::Lit(val) => write!(f, "(-{})", val.magnitude()),
+            Ast::Neg(ast) if f.alternate() => write!(f, "(- {:#})", ast),
+            Ast::Neg(ast) => write!(f, "-{}", ast),
+            Ast::Binary(op, lhs, rhs) if f.alternate() => write!(f, "({} {:#} {:#})", op, lhs, rhs),
+            Ast::Binary(op, lhs, rhs) => write!(f, "({} {} {})", lhs, op, rhs),
         }
     }
 }
 
+/// Group the expression using the operator precedences
+fn ast(expr: &Expr, precedence: &Precedence) -> Result<Ast, EvalError> {
+    let exprs = match expr {
+        Expr::Brackets(exprs) => exprs.as_slice(),
+        _ => std::slice::from_ref(expr),
+    };
+    Ast::parse(exprs, precedence)
+}
+
+/// Evaluate the expression using the operator precedences
+fn evaluate(expr: &Expr, precedence: &Precedence) -> Result<BigInt, EvalError> {
+    ast(expr, precedence)?.eval()
+}
+
+/// Sum up all expressions, failing on the first error
+fn sum(input: &[Expr], precedence: &Precedence) -> Result<BigInt, EvalError> {
+    input.iter().try_fold(BigInt::from(0), |acc, expr| {
+        Ok(acc + evaluate(expr, precedence)?)
+    })
+}
+
 #[aoc_generator(day18)]
-fn input_generator(input: &str) -> Vec<Expr> {
+fn input_generator(input: &str) -> Result<Vec<Expr>, ParseError> {
+    let ctx = ParseContext::new(18, input);
     input
         .lines()
         .map(|line| {
-            let mut chars = line.chars().filter(|c| !c.is_whitespace()).peekable();
-            Expr::Brackets(parse_expr_list(&mut chars))
+            let mut chars = line
+                .char_indices()
+                .filter(|(_, c)| !c.is_whitespace())
+                .peekable();
+            let exprs = parse_expr_list(&ctx, line, &mut chars)?;
+            match chars.next() {
+                None => Ok(Expr::Brackets(exprs)),
+                Some((idx, _)) => Err(char_error(
+                    &ctx,
+                    line,
+                    idx,
+                    "expected operator or end of line, got",
+                )),
+            }
         })
         .collect()
 }
 
-fn parse_expr(chars: &mut Peekable<impl Iterator<Item = char>>) -> Expr {
+/// Create an error pointing at the char at `idx` or at the end of the line
+fn char_error(ctx: &ParseContext<'_>, line: &str, idx: usize, reason: &str) -> ParseError {
+    let len = line[idx..].chars().next().map_or(0, char::len_utf8);
+    ctx.error(&line[idx..idx + len], reason)
+}
+
+fn parse_expr(
+    ctx: &ParseContext<'_>,
+    line: &str,
+    chars: &mut Peekable<impl Iterator<Item = (usize, char)>>,
+) -> Result<Expr, ParseError> {
     match chars.peek() {
-        Some('(') => {
+        Some(&(_, '(')) => {
             // Consume bracket
             chars.by_ref().next();
-            let res = Expr::Brackets(parse_expr_list(chars));
-            let close_bracket = chars.next();
-            assert_eq!(Some(')'), close_bracket);
-            res
+            let res = Expr::Brackets(parse_expr_list(ctx, line, chars)?);
+            match chars.next() {
+                Some((_, ')')) => Ok(res),
+                Some((idx, _)) => Err(char_error(ctx, line, idx, "expected `)`, got")),
+                None => Err(char_error(
+                    ctx,
+                    line,
+                    line.len(),
+                    "missing `)` at end of line",
+                )),
+            }
         }
-        Some(d) if d.is_ascii_digit() => Expr::Lit(parse_lit(chars)),
-        Some(c) => panic!("Unknown char {}", c),
-        None => panic!("Unexpected end of iterator"),
+        Some(&(_, d)) if d.is_ascii_digit() => Ok(Expr::Lit(parse_lit(ctx, line, chars)?)),
+        Some(&(idx, _)) => Err(char_error(ctx, line, idx, "unknown char")),
+        None => Err(char_error(ctx, line, line.len(), "unexpected end of line")),
     }
 }
 
-fn parse_expr_list(chars: &mut Peekable<impl Iterator<Item = char>>) -> Vec<Expr> {
+fn parse_expr_list(
+    ctx: &ParseContext<'_>,
+    line: &str,
+    chars: &mut Peekable<impl Iterator<Item = (usize, char)>>,
+) -> Result<Vec<Expr>, ParseError> {
     let mut exprs = vec![];
-    exprs.push(parse_expr(chars));
-    while let Some(&op) = chars.peek() {
-        if op == '*' || op == '+' {
-            // Consume op char
+    loop {
+        // Unary minus in front of the operand
+        while let Some(&(_, '-')) = chars.peek() {
             chars.next();
-            exprs.push(Expr::Op(op));
-            exprs.push(parse_expr(chars))
-        } else {
-            break;
+            exprs.push(Expr::Op('-'));
+        }
+        exprs.push(parse_expr(ctx, line, chars)?);
+        match chars.peek() {
+            Some(&(_, op)) if "+-*/".contains(op) => {
+                // Consume op char
+                chars.next();
+                exprs.push(Expr::Op(op));
+            }
+            _ => break,
         }
     }
-    exprs
+    Ok(exprs)
 }
 
-fn parse_lit(chars: &mut Peekable<impl Iterator<Item = char>>) -> i64 {
-    let mut buffer = String::with_capacity(5);
-    while chars.peek().is_some() && chars.peek().unwrap().is_ascii_digit() {
-        let d = chars.next().unwrap();
-        buffer.push(d);
+fn parse_lit(
+    ctx: &ParseContext<'_>,
+    line: &str,
+    chars: &mut Peekable<impl Iterator<Item = (usize, char)>>,
+) -> Result<BigInt, ParseError> {
+    let start = chars.peek().map_or(line.len(), |&(idx, _)| idx);
+    let mut end = start;
+    while let Some(&(idx, d)) = chars.peek() {
+        // Whitespace is filtered out, so a gap in the indices ends the literal
+        if !d.is_ascii_digit() || idx != end {
+            break;
+        }
+        chars.next();
+        end = idx + 1;
     }
-    buffer.parse().expect("Could not parse literal buffer")
+    ctx.parse(&line[start..end])
 }
 
 #[aoc(day18, part1)]
-fn part1(input: &[Expr]) -> i64 {
-    input
-        .iter()
-        .map(|expr| expr.clone().simplify_part1().as_val())
-        .sum()
+fn part1(input: &[Expr]) -> Result<BigInt, EvalError> {
+    sum(input, &PART1)
 }
 
 #[aoc(day18, part2)]
-fn part2(input: &[Expr]) -> i64 {
-    input
-        .iter()
-        .map(|expr| expr.clone().simplify_part2().as_val())
-        .sum()
+fn part2(input: &[Expr]) -> Result<BigInt, EvalError> {
+    sum(input, &PART2)
 }
 
 #[test]
 fn test_formula0() {
     use Expr::*;
-    let values = input_generator("1 + 2 * 3 + 4 * 5 + 6)");
+    let values = input_generator("1 + 2 * 3 + 4 * 5 + 6").unwrap();
     assert_eq!(
         vec![Brackets(vec![
-            Lit(1),
+            lit(1),
             Op('+'),
-            Lit(2),
+            lit(2),
             Op('*'),
-            Lit(3),
+            lit(3),
             Op('+'),
-            Lit(4),
+            lit(4),
             Op('*'),
-            Lit(5),
+            lit(5),
             Op('+'),
-            Lit(6)
+            lit(6)
         ])],
         values
     );
-    assert_eq!(71, values[0].clone().simplify_part1().as_val());
-    assert_eq!(231, values[0].clone().simplify_part2().as_val());
+    assert_eq!(Ok(BigInt::from(71)), evaluate(&values[0], &PART1));
+    assert_eq!(Ok(BigInt::from(231)), evaluate(&values[0], &PART2));
 }
 
 #[test]
 fn test_formula1() {
     use Expr::*;
-    let values = input_generator("1 + (2 * 3) + (4 * (5 + 6))");
+    let values = input_generator("1 + (2 * 3) + (4 * (5 + 6))").unwrap();
     assert_eq!(
         vec![Brackets(vec![
-            Lit(1),
+            lit(1),
             Op('+'),
-            Brackets(vec![Lit(2), Op('*'), Lit(3)]),
+            Brackets(vec![lit(2), Op('*'), lit(3)]),
             Op('+'),
             Brackets(vec![
-                Lit(4),
+                lit(4),
                 Op('*'),
-                Brackets(vec![Lit(5), Op('+'), Lit(6)])
+                Brackets(vec![lit(5), Op('+'), lit(6)])
             ])
         ])],
         values
     );
-    assert_eq!(51, values[0].clone().simplify_part1().as_val());
-    assert_eq!(51, values[0].clone().simplify_part2().as_val());
+    assert_eq!(Ok(BigInt::from(51)), evaluate(&values[0], &PART1));
+    assert_eq!(Ok(BigInt::from(51)), evaluate(&values[0], &PART2));
 }
 
 #[test]
 fn test_formula2() {
     use Expr::*;
-    let values = input_generator("2 * 3 + (4 * 5)");
+    let values = input_generator("2 * 3 + (4 * 5)").unwrap();
     assert_eq!(
         vec![Brackets(vec![
-            Lit(2),
+            lit(2),
             Op('*'),
-            Lit(3),
+            lit(3),
             Op('+'),
-            Brackets(vec![Lit(4), Op('*'), Lit(5)])
+            Brackets(vec![lit(4), Op('*'), lit(5)])
         ])],
         values
     );
-    assert_eq!(26, values[0].clone().simplify_part1().as_val());
-    assert_eq!(46, values[0].clone().simplify_part2().as_val());
+    assert_eq!(Ok(BigInt::from(26)), evaluate(&values[0], &PART1));
+    assert_eq!(Ok(BigInt::from(46)), evaluate(&values[0], &PART2));
 }
 
 #[test]
 fn test_formula3() {
     use Expr::*;
-    let values = input_generator("5 + (8 * 3 + 9 + 3 * 4 * 3)");
+    let values = input_generator("5 + (8 * 3 + 9 + 3 * 4 * 3)").unwrap();
     assert_eq!(
         vec![Brackets(vec![
-            Lit(5),
+            lit(5),
             Op('+'),
             Brackets(vec![
-                Lit(8),
+                lit(8),
                 Op('*'),
-                Lit(3),
+                lit(3),
                 Op('+'),
-                Lit(9),
+                lit(9),
                 Op('+'),
-                Lit(3),
+                lit(3),
                 Op('*'),
-                Lit(4),
+                lit(4),
                 Op('*'),
-                Lit(3)
+                lit(3)
             ])
         ])],
         values
     );
-    assert_eq!(437, values[0].clone().simplify_part1().as_val());
-    assert_eq!(1445, values[0].clone().simplify_part2().as_val());
+    assert_eq!(Ok(BigInt::from(437)), evaluate(&values[0], &PART1));
+    assert_eq!(Ok(BigInt::from(1445)), evaluate(&values[0], &PART2));
 }
 
 #[test]
 fn test_formula4() {
     use Expr::*;
-    let values = input_generator("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))");
+    let values = input_generator("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))").unwrap();
     assert_eq!(
         vec![Brackets(vec![
-            Lit(5),
+            lit(5),
             Op('*'),
-            Lit(9),
+            lit(9),
             Op('*'),
             Brackets(vec![
-                Lit(7),
+                lit(7),
                 Op('*'),
-                Lit(3),
+                lit(3),
                 Op('*'),
-                Lit(3),
+                lit(3),
                 Op('+'),
-                Lit(9),
+                lit(9),
                 Op('*'),
-                Lit(3),
+                lit(3),
                 Op('+'),
-                Brackets(vec![Lit(8), Op('+'), Lit(6), Op('*'), Lit(4)])
+                Brackets(vec![lit(8), Op('+'), lit(6), Op('*'), lit(4)])
             ])
         ])],
         values
     );
-    assert_eq!(12240, values[0].clone().simplify_part1().as_val());
-    assert_eq!(669060, values[0].clone().simplify_part2().as_val());
+    assert_eq!(Ok(BigInt::from(12240)), evaluate(&values[0], &PART1));
+    assert_eq!(Ok(BigInt::from(669060)), evaluate(&values[0], &PART2));
 }
 
 #[test]
 fn test_formula5() {
     use Expr::*;
-    let values = input_generator("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2");
+    let values = input_generator("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2").unwrap();
     assert_eq!(
         vec![Brackets(vec![
             Brackets(vec![
-                Brackets(vec![Lit(2), Op('+'), Lit(4), Op('*'), Lit(9)]),
+                Brackets(vec![lit(2), Op('+'), lit(4), Op('*'), lit(9)]),
                 Op('*'),
                 Brackets(vec![
-                    Lit(6),
+                    lit(6),
                     Op('+'),
-                    Lit(9),
+                    lit(9),
                     Op('*'),
-                    Lit(8),
+                    lit(8),
                     Op('+'),
-                    Lit(6)
+                    lit(6)
                 ]),
                 Op('+'),
-                Lit(6)
+                lit(6)
             ]),
             Op('+'),
-            Lit(2),
+            lit(2),
             Op('+'),
-            Lit(4),
+            lit(4),
             Op('*'),
-            Lit(2)
+            lit(2)
         ])],
         values
     );
-    assert_eq!(13632, values[0].clone().simplify_part1().as_val());
-    assert_eq!(23340, values[0].clone().simplify_part2().as_val());
+    assert_eq!(Ok(BigInt::from(13632)), evaluate(&values[0], &PART1));
+    assert_eq!(Ok(BigInt::from(23340)), evaluate(&values[0], &PART2));
 }
 
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
-    assert_eq!(26 + 437 + 12240 + 13632, part1(&values));
+    let values = input_generator(PUZZLE).unwrap();
+    assert_eq!(Ok(BigInt::from(26 + 437 + 12240 + 13632)), part1(&values));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day18.txt").trim());
-    assert_eq!(202553439706, part1(&values));
+    let values = input_generator(include_str!("../input/2020/day18.txt").trim()).unwrap();
+    assert_eq!(Ok(BigInt::from(202553439706_i64)), part1(&values));
 }
 
 #[test]
 fn test_part2() {
-    let values = input_generator(PUZZLE);
-    assert_eq!(46 + 1445 + 669060 + 23340, part2(&values));
+    let values = input_generator(PUZZLE).unwrap();
+    assert_eq!(Ok(BigInt::from(46 + 1445 + 669060 + 23340)), part2(&values));
 }
 
 #[test]
 fn test_part2_solution() {
-    let values = input_generator(include_str!("../input/2020/day18.txt").trim());
-    assert_eq!(88534268715686, part2(&values));
+    let values = input_generator(include_str!("../input/2020/day18.txt").trim()).unwrap();
+    assert_eq!(Ok(BigInt::from(88534268715686_i64)), part2(&values));
+}
+
+#[test]
+fn test_arithmetic() {
+    let eval = |line: &str, precedence| evaluate(&input_generator(line).unwrap()[0], precedence);
+    assert_eq!(Ok(BigInt::from(14)), eval("2 + 3 * 4", &ARITHMETIC));
+    assert_eq!(Ok(BigInt::from(20)), eval("2 + 3 * 4", &PART1));
+    assert_eq!(Ok(BigInt::from(3)), eval("10 - 4 - 3", &ARITHMETIC));
+    assert_eq!(Ok(BigInt::from(2)), eval("20 / 5 / 2", &ARITHMETIC));
+    assert_eq!(Ok(BigInt::from(-3)), eval("-7 / 2", &ARITHMETIC));
+    assert_eq!(Ok(BigInt::from(-8)), eval("-(2 * 4)", &ARITHMETIC));
+    assert_eq!(Ok(BigInt::from(5)), eval("--5", &ARITHMETIC));
+    assert_eq!(Ok(BigInt::from(-6)), eval("2 * -3", &PART2));
+    assert_eq!(Ok(BigInt::from(2)), eval("-2 + 3 * 2", &PART2));
+}
+
+#[test]
+fn test_big_numbers() {
+    let eval = |line: &str| {
+        let value = evaluate(&input_generator(line).unwrap()[0], &ARITHMETIC).unwrap();
+        value.to_string()
+    };
+    assert_eq!("9223372036854775808", eval("9223372036854775807 + 1"));
+    assert_eq!("9223372037000250000", eval("3037000500 * 3037000500"));
+    assert_eq!(
+        "-340282366920938463463374607431768211456",
+        eval("-18446744073709551616 * 18446744073709551616")
+    );
+    assert_eq!(
+        "-18446744073709551615",
+        eval("-(340282366920938463463374607431768211455 / 18446744073709551617)")
+    );
+}
+
+#[test]
+fn test_errors() {
+    let eval = |line: &str| evaluate(&input_generator(line).unwrap()[0], &ARITHMETIC);
+    assert_eq!(Err(EvalError::DivisionByZero), eval("1 / (2 - 2)"));
+
+    use Expr::*;
+    let no_precedence = Precedence(&[('+', 1)]);
+    assert_eq!(
+        Err(EvalError::UnknownOperator('*')),
+        evaluate(&Brackets(vec![lit(1), Op('*'), lit(2)]), &no_precedence)
+    );
+    assert_eq!(
+        Err(EvalError::MissingOperator),
+        evaluate(&Brackets(vec![lit(1), lit(2)]), &ARITHMETIC)
+    );
+    assert_eq!(
+        Err(EvalError::MissingOperand),
+        evaluate(&Brackets(vec![lit(1), Op('+')]), &ARITHMETIC)
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    let error = |input: &str| input_generator(input).unwrap_err().to_string();
+    assert_eq!(
+        "day18 line 1 column 6: expected operator or end of line, got ')'",
+        error("1 + 2) * 100")
+    );
+    assert_eq!(
+        "day18 line 1 column 13: expected operator or end of line, got '7'",
+        error("1 + (2 * 3) 7")
+    );
+    assert_eq!(
+        "day18 line 2 column 3: expected operator or end of line, got '3'",
+        error("1\n2 3")
+    );
+    assert_eq!(
+        "day18 line 1 column 8: expected `)`, got '3'",
+        error("(1 + 2 3)")
+    );
+    assert_eq!(
+        "day18 line 1 column 7: missing `)` at end of line ''",
+        error("(1 + 2")
+    );
+    assert_eq!("day18 line 1 column 5: unknown char 'x'", error("1 + x"));
+}
+
+#[test]
+fn test_print() {
+    let values = input_generator("2 * 3 + -(4 * 5)").unwrap();
+    let part1 = ast(&values[0], &PART1).unwrap();
+    assert_eq!("((2 * 3) + -(4 * 5))", part1.to_string());
+    assert_eq!("(+ (* 2 3) (- (* 4 5)))", format!("{:#}", part1));
+    let part2 = ast(&values[0], &PART2).unwrap();
+    assert_eq!("(2 * (3 + -(4 * 5)))", part2.to_string());
+    assert_eq!("(* 2 (+ 3 (- (* 4 5))))", format!("{:#}", part2));
+
+    let negative = Ast::Binary(
+        '-',
+        Box::new(Ast::Lit(BigInt::from(1))),
+        Box::new(Ast::Lit(BigInt::from(-2))),
+    );
+    assert_eq!("(1 - (-2))", negative.to_string());
+    assert_eq!("(- 1 -2)", format!("{:#}", negative));
+}
+
+/// The printed expression must parse to the same tree and thus the same value, even with different precedences
+#[test]
+fn test_print_round_trip() {
+    let values = input_generator(include_str!("../input/2020/day18.txt").trim()).unwrap();
+    for expr in &values {
+        for precedence in &[&PART1, &PART2, &ARITHMETIC] {
+            let ast = ast(expr, precedence).unwrap();
+            let printed = ast.to_string();
+            let reparsed = &input_generator(&printed).unwrap()[0];
+            for other in &[&PART1, &PART2, &ARITHMETIC] {
+                assert_eq!(
+                    ast,
+                    self::ast(reparsed, other).unwrap(),
+                    "{} does not round-trip",
+                    printed
+                );
+                assert_eq!(ast.eval(), evaluate(reparsed, other));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+fn lit(val: i64) -> Expr {
+    Expr::Lit(BigInt::from(val))
 }
 
 #[cfg(test)]
@@ -1,132 +1,300 @@
+use crate::dot::Dot;
 use crate::prelude::*;
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Rule {
+    /// Matches exactly this single character
+    Char(char),
+    /// Matches if any of the sequences of sub-rules match
+    Alternatives(Vec<Vec<u32>>),
+}
+
+impl std::str::FromStr for Rule {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        if let Some(c) = pattern
+            .strip_prefix('"')
+            .and_then(|p| p.strip_suffix('"'))
+            .and_then(|p| p.chars().exactly_one().ok())
+        {
+            return Ok(Rule::Char(c));
+        }
+        let alternatives = pattern
+            .split(" | ")
+            .map(|seq| seq.split(' ').map(str::parse).collect())
+            .collect::<Result<_, _>>()?;
+        Ok(Rule::Alternatives(alternatives))
+    }
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rule::Char(c) => write!(f, "\"{}\"", c),
+            Rule::Alternatives(alternatives) => write!(
+                f,
+                "{}",
+                alternatives
+                    .iter()
+                    .map(|seq| seq.iter().join(" "))
+                    .join(" | ")
+            ),
+        }
+    }
+}
+
 struct Input {
-    rules: Map<u32, String>,
+    rules: Map<u32, Rule>,
     messages: Vec<String>,
 }
 
+impl Input {
+    /// Graphviz graph of the rules, where the edges are labeled with the alternative they belong to
+    ///
+    /// The rule `highlight` and all rules it refers to are highlighted.
+    fn to_dot(&self, highlight: Option<u32>) -> String {
+        let mut highlighted = Set::new();
+        let mut unexplored = highlight.into_iter().collect_vec();
+        while let Some(id) = unexplored.pop() {
+            if highlighted.insert(id) {
+                if let Some(Rule::Alternatives(alternatives)) = self.rules.get(&id) {
+                    unexplored.extend(alternatives.iter().flatten());
+                }
+            }
+        }
+
+        let mut dot = Dot::new("rules");
+        for (id, rule) in &self.rules {
+            let label = format!("{}: {}", id, rule);
+            dot.node(&id.to_string(), &label, highlighted.contains(id));
+        }
+        for (id, rule) in &self.rules {
+            if let Rule::Alternatives(alternatives) = rule {
+                for seq in alternatives {
+                    let label = seq.iter().join(" ");
+                    for sub in seq.iter().unique() {
+                        dot.edge(
+                            &id.to_string(),
+                            &sub.to_string(),
+                            &label,
+                            highlighted.contains(id),
+                        );
+                    }
+                }
+            }
+        }
+        dot.finish()
+    }
+}
+
 #[aoc_generator(day19)]
-fn input_generator(input: &str) -> Input {
-    let mut input = input.split("\n\n");
-    let rules: Map<u32, String> = input
-        .next()
-        .unwrap()
+fn input_generator(input: &str) -> Result<Input, ParseError> {
+    let ctx = ParseContext::new(19, input);
+    let mut parts = input.split("\n\n");
+    let rule_lines = parts.next().unwrap_or_default();
+    let rules: Map<u32, Rule> = rule_lines
         .lines()
         .map(|line| {
             let mut i = line.split(": ");
-            let id: u32 = i.next().unwrap().parse().unwrap();
-            let pattern = i.next().unwrap().to_string();
-            (id, pattern)
+            let id: u32 = ctx.parse(i.next().unwrap_or_default())?;
+            let pattern = ctx.require(i.next(), line, "`: ` after rule id")?;
+            let pattern = pattern
+                .parse()
+                .map_err(|_| ctx.error(pattern, "invalid rule pattern"))?;
+            Ok((id, pattern))
         })
-        .collect();
-    let messages = input
-        .next()
-        .unwrap()
+        .collect::<Result<_, _>>()?;
+    // Matching looks up rules by id, so every referenced rule must exist
+    ctx.require(rules.get(&0), rule_lines, "rule 0")?;
+    for line in rule_lines.lines() {
+        let pattern = line.split(": ").nth(1).unwrap_or_default();
+        for sub in pattern.split(' ') {
+            if let Ok(sub_id) = sub.parse::<u32>() {
+                if !rules.contains_key(&sub_id) {
+                    return Err(ctx.error(sub, "undefined rule"));
+                }
+            }
+        }
+    }
+    let messages = ctx
+        .require(parts.next(), input, "messages after the rules")?
         .lines()
         .map(ToString::to_string)
         .collect();
 
-    Input { rules, messages }
+    Ok(Input { rules, messages })
 }
 
-fn convert_rule(
-    id: u32,
-    rules: &mut Map<u32, Set<String>>,
-    tmp_rules: &Map<u32, String>,
-) -> Set<String> {
-    if let Some(res) = rules.get(&id) {
-        return res.clone();
-    }
-
-    let pattern = &tmp_rules[&id];
-    let pattern: BTreeSet<String> = if pattern == "\"a\"" {
-        vec!["a".to_string()].into_iter().collect()
-    } else if pattern == "\"b\"" {
-        vec!["b".to_string()].into_iter().collect()
-    } else {
-        pattern
-            .split(" | ")
-            .flat_map(|p| {
-                let mut patterns = Set::new();
-                patterns.insert("".to_string());
-                for other_pattern in p
-                    .split(' ')
-                    .map(|id| convert_rule(id.parse().unwrap(), rules, tmp_rules))
-                {
-                    patterns = merge_patterns(&patterns, &other_pattern);
-                }
-                patterns
-            })
-            .collect()
-    };
-    rules.insert(id, pattern.clone());
-    pattern
-}
-
-fn merge_patterns(a: &Set<String>, b: &Set<String>) -> Set<String> {
-    let mut res = Set::new();
-    for string_a in a {
-        for string_b in b {
-            let mut new_str = String::with_capacity(string_a.len() + string_b.len());
-            new_str.push_str(&string_a);
-            new_str.push_str(&string_b);
-            res.insert(new_str);
-            // res.insert(format!("{}{}", string_a, string_b));
+/// Return all positions in `msg` at which a match of rule `id` starting at `pos` can end.
+///
+/// The matcher backtracks over all alternatives, so recursive rules like `8: 42 | 42 8` are supported.
+/// It only terminates if the grammar is not left-recursive, i.e., every recursive use of a rule must be preceded by a rule consuming at least one character.
+fn match_rule(rules: &Map<u32, Rule>, id: u32, msg: &[char], pos: usize) -> Set<usize> {
+    match &rules[&id] {
+        Rule::Char(c) => {
+            if msg.get(pos) == Some(c) {
+                Some(pos + 1).into_iter().collect()
+            } else {
+                Set::new()
+            }
         }
+        Rule::Alternatives(alternatives) => alternatives
+            .iter()
+            .flat_map(|seq| {
+                seq.iter().fold(
+                    Some(pos).into_iter().collect(),
+                    |ends: Set<usize>, &sub_id| {
+                        ends.into_iter()
+                            .filter(|&end| end < msg.len())
+                            .flat_map(|end| match_rule(rules, sub_id, msg, end))
+                            .collect()
+                    },
+                )
+            })
+            .collect(),
     }
-    res
+}
+
+/// Check if the whole message matches rule 0
+fn is_match(rules: &Map<u32, Rule>, msg: &str) -> bool {
+    let msg = msg.chars().collect_vec();
+    match_rule(rules, 0, &msg, 0).contains(&msg.len())
 }
 
 #[aoc(day19, part1)]
 fn part1(input: &Input) -> usize {
-    let mut rules = Map::new();
-    convert_rule(0, &mut rules, &input.rules);
-    let rule = &input.rules[&0];
     input
         .messages
         .iter()
-        .filter(|&msg| rule.contains(msg))
+        .filter(|msg| is_match(&input.rules, msg))
         .count()
 }
 
+/// The rules with the loops of part 2
+fn looping_rules(rules: &Map<u32, Rule>) -> Map<u32, Rule> {
+    let mut rules = rules.clone();
+    rules.insert(8, "42 | 42 8".parse().unwrap());
+    rules.insert(11, "42 31 | 42 11 31".parse().unwrap());
+    rules
+}
+
+/// Draw the rules of both parts as Graphviz graphs, highlighting all rules reachable from rule 0
+pub fn graphs(input: &str) -> Result<Vec<(&'static str, String)>, ParseError> {
+    let input = input_generator(input)?;
+    let part2 = Input {
+        rules: looping_rules(&input.rules),
+        messages: Vec::new(),
+    };
+    Ok(vec![
+        ("part1", input.to_dot(Some(0))),
+        ("part2", part2.to_dot(Some(0))),
+    ])
+}
+
 #[aoc(day19, part2)]
 fn part2(input: &Input) -> usize {
-    let mut tmp_rules = input.rules.clone();
-    tmp_rules.insert(8, "42 | 42 8".to_string());
-    tmp_rules.insert(11, "42 31 | 42 11 31".to_string());
-    let mut rules = Map::new();
-    convert_rule(0, &mut rules, &input.rules);
-    let rule = &input.rules[&0];
+    let rules = looping_rules(&input.rules);
     input
         .messages
         .iter()
-        .filter(|&msg| rule.contains(msg))
+        .filter(|msg| is_match(&rules, msg))
         .count()
 }
 
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(2, part1(&values));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day19.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day19.txt").trim()).unwrap();
     assert_eq!(122, part1(&values));
 }
 
-// #[test]
-// fn test_part2() {
-//     let values = input_generator(PUZZLE);
-//     assert_eq!(46 + 1445 + 669060 + 23340, part2(&values));
-// }
+#[test]
+fn test_part1_large() {
+    let values = input_generator(PUZZLE_PART2).unwrap();
+    assert_eq!(3, part1(&values));
+}
 
-// #[test]
-// fn test_part2_solution() {
-//     let values = input_generator(include_str!("../input/2020/day19.txt").trim());
-//     assert_eq!(88534268715686, part2(&values));
-// }
+#[test]
+fn test_part2() {
+    let values = input_generator(PUZZLE_PART2).unwrap();
+    assert_eq!(12, part2(&values));
+}
+
+#[test]
+fn test_part2_solution() {
+    let values = input_generator(include_str!("../input/2020/day19.txt").trim()).unwrap();
+    assert_eq!(287, part2(&values));
+}
+
+#[test]
+fn test_undefined_rules() {
+    let error = |input: &str| input_generator(input).err().unwrap().to_string();
+    assert_eq!(
+        "day19 line 2 column 6: undefined rule '3'",
+        error("0: 1 2\n1: 2 3 | 2\n2: \"a\"\n\na")
+    );
+    assert_eq!(
+        "day19 line 1 column 1: missing rule 0 in '1: \"a\"'",
+        error("1: \"a\"\n\na")
+    );
+}
+
+#[test]
+fn test_to_dot() {
+    let values = input_generator(PUZZLE).unwrap();
+    assert_eq!(
+        r#"digraph "rules" {
+    "0" [label="0: 4 1 5"];
+    "1" [label="1: 2 3 | 3 2"];
+    "2" [label="2: 4 4 | 5 5"];
+    "3" [label="3: 4 5 | 5 4", color=red, fontcolor=red];
+    "4" [label="4: \"a\"", color=red, fontcolor=red];
+    "5" [label="5: \"b\"", color=red, fontcolor=red];
+    "0" -> "4" [label="4 1 5"];
+    "0" -> "1" [label="4 1 5"];
+    "0" -> "5" [label="4 1 5"];
+    "1" -> "2" [label="2 3"];
+    "1" -> "3" [label="2 3"];
+    "1" -> "3" [label="3 2"];
+    "1" -> "2" [label="3 2"];
+    "2" -> "4" [label="4 4"];
+    "2" -> "5" [label="5 5"];
+    "3" -> "4" [label="4 5", color=red, fontcolor=red];
+    "3" -> "5" [label="4 5", color=red, fontcolor=red];
+    "3" -> "5" [label="5 4", color=red, fontcolor=red];
+    "3" -> "4" [label="5 4", color=red, fontcolor=red];
+}
+"#,
+        values.to_dot(Some(3))
+    );
+    // Rule 0 reaches all rules
+    assert!(!values.to_dot(Some(0)).contains("\"];"));
+}
+
+#[test]
+fn test_graphs() {
+    let graphs = graphs(PUZZLE_PART2).unwrap();
+    assert_eq!(
+        vec!["part1", "part2"],
+        graphs.iter().map(|(name, _)| *name).collect_vec()
+    );
+    assert!(graphs[0]
+        .1
+        .contains(r#""8" [label="8: 42", color=red, fontcolor=red];"#));
+    assert!(!graphs[0].1.contains(r#""8" -> "8""#));
+    assert!(graphs[1]
+        .1
+        .contains(r#""8" [label="8: 42 | 42 8", color=red, fontcolor=red];"#));
+    assert!(graphs[1]
+        .1
+        .contains(r#""8" -> "8" [label="42 8", color=red, fontcolor=red];"#));
+}
 
 #[cfg(test)]
 static PUZZLE: &str = r#"0: 4 1 5
@@ -141,3 +309,52 @@ bababa
 abbbab
 aaabbb
 aaaabbb"#;
+
+#[cfg(test)]
+static PUZZLE_PART2: &str = r#"42: 9 14 | 10 1
+9: 14 27 | 1 26
+10: 23 14 | 28 1
+1: "a"
+11: 42 31
+5: 1 14 | 15 1
+19: 14 1 | 14 14
+12: 24 14 | 19 1
+16: 15 1 | 14 14
+31: 14 17 | 1 13
+6: 14 14 | 1 14
+2: 1 24 | 14 4
+0: 8 11
+13: 14 3 | 1 12
+15: 1 | 14
+17: 14 2 | 1 7
+23: 25 1 | 22 14
+28: 16 1
+4: 1 1
+20: 14 14 | 1 15
+3: 5 14 | 16 1
+27: 1 6 | 14 18
+14: "b"
+21: 14 1 | 1 14
+25: 1 1 | 1 14
+22: 14 14
+8: 42
+26: 14 22 | 1 20
+18: 15 15
+7: 14 5 | 1 21
+24: 14 1
+
+abbbbbabbbaaaababbaabbbbabababbbabbbbbbabaaaa
+bbabbbbaabaabba
+babbbbaabbbbbabbbbbbaabaaabaaa
+aaabbbbbbaaaabaababaabababbabaaabbababababaaa
+bbbbbbbaaaabbbbaaabbabaaa
+bbbababbbbaaaaaaaabbababaaababaabab
+ababaaaaaabaaab
+ababaaaaabbbaba
+baabbaaaabbaaaababbaababb
+abbbbabbbbaaaababbbbbbaaaababb
+aaaaabbaabaaaaababaa
+aaaabbaaaabbaaa
+aaaabbaabbaaaaaaabbbabbbaaabbaabaaa
+babaaabbbaaabaababbaabababaaab
+aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba"#;
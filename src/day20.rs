@@ -1,92 +1,132 @@
+use crate::grid::Grid;
 use crate::prelude::*;
-use std::convert::TryInto;
 
+/// The sea monster which needs to be found in the assembled image
+static SEA_MONSTER: &str = r#"                  # 
+#    ##    ##    ###
+ #  #  #  #  #  #   "#;
+
+#[derive(Clone)]
 struct Tile {
     id: u64,
-    pixels: [[bool; 10]; 10],
+    pixels: Grid<bool>,
 }
 
 impl Tile {
-    fn borders(&self) -> [[bool; 10]; 8] {
-        let tmp = [
-            self.pixels[0],
-            {
-                (0..10)
-                    .map(|i| self.pixels[i][0])
-                    .collect_vec()
-                    .try_into()
-                    .unwrap()
-            },
-            {
-                let mut tmp = self.pixels[9];
-                tmp.reverse();
-                tmp
-            },
-            {
-                (0..10)
-                    .rev()
-                    .map(|i| self.pixels[i][9])
-                    .collect_vec()
-                    .try_into()
-                    .unwrap()
-            },
+    /// Return the tile in all 8 possible orientations
+    fn orientations(&self) -> impl Iterator<Item = Self> + '_ {
+        self.pixels.orientations().map(move |pixels| Tile {
+            id: self.id,
+            pixels,
+        })
+    }
+
+    fn top(&self) -> Vec<bool> {
+        self.pixels.row(0).to_vec()
+    }
+
+    fn bottom(&self) -> Vec<bool> {
+        self.pixels.row(self.pixels.height() - 1).to_vec()
+    }
+
+    fn left(&self) -> Vec<bool> {
+        self.pixels.column(0).copied().collect()
+    }
+
+    fn right(&self) -> Vec<bool> {
+        self.pixels
+            .column(self.pixels.width() - 1)
+            .copied()
+            .collect()
+    }
+
+    /// All borders read clockwise around the tile, followed by the same borders reversed
+    fn borders(&self) -> Vec<Vec<bool>> {
+        let rev = |mut border: Vec<bool>| {
+            border.reverse();
+            border
+        };
+        let clockwise = vec![
+            self.top(),
+            self.right(),
+            rev(self.bottom()),
+            rev(self.left()),
         ];
-        [
-            tmp[0],
-            tmp[1],
-            tmp[2],
-            tmp[3],
-            {
-                let mut x = tmp[0];
-                x.reverse();
-                x
-            },
-            {
-                let mut x = tmp[1];
-                x.reverse();
-                x
-            },
-            {
-                let mut x = tmp[2];
-                x.reverse();
-                x
-            },
-            {
-                let mut x = tmp[3];
-                x.reverse();
-                x
-            },
-        ]
+        let counter_clockwise = clockwise.iter().cloned().map(rev).collect_vec();
+        clockwise.into_iter().chain(counter_clockwise).collect()
+    }
+}
+
+/// The fully assembled image with all tile borders removed
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Image {
+    pixels: Grid<bool>,
+}
+
+impl Image {
+    /// Find all sea monsters and return the set of pixels which are part of any sea monster
+    fn find_sea_monsters(&self) -> Set<(usize, usize)> {
+        let monster: Grid<char> = Grid::parse(&ParseContext::new(20, SEA_MONSTER), SEA_MONSTER)
+            .expect("The sea monster is a valid grid");
+        let monster: Vec<(usize, usize)> = monster
+            .positions()
+            .filter(|&pos| monster[pos] == '#')
+            .collect();
+        let monster_width = monster.iter().map(|&(x, _)| x + 1).max().unwrap();
+        let monster_height = monster.iter().map(|&(_, y)| y + 1).max().unwrap();
+
+        let mut res = Set::new();
+        for y in 0..=(self.pixels.height().saturating_sub(monster_height)) {
+            for x in 0..=(self.pixels.width().saturating_sub(monster_width)) {
+                let view = self.pixels.view((x, y), monster_width, monster_height);
+                if monster.iter().all(|&pos| view[pos]) {
+                    res.extend(monster.iter().map(|&(mx, my)| (x + mx, y + my)));
+                }
+            }
+        }
+        res
+    }
+
+    /// Count all `#` which are not part of any sea monster
+    ///
+    /// All orientations of the image are tried, until one is found which contains sea monsters.
+    fn water_roughness(&self) -> usize {
+        let total = self.pixels.iter().filter(|&&p| p).count();
+        self.pixels
+            .orientations()
+            .map(|pixels| Image { pixels }.find_sea_monsters().len())
+            .find(|&monster_pixels| monster_pixels > 0)
+            .map(|monster_pixels| total - monster_pixels)
+            .expect("No orientation of the image contains sea monsters")
+    }
+}
+
+impl std::fmt::Display for Image {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.pixels.fmt(f)
     }
 }
 
 #[aoc_generator(day20)]
-fn input_generator(input: &str) -> Vec<Tile> {
+fn input_generator(input: &str) -> Result<Vec<Tile>, ParseError> {
+    let ctx = ParseContext::new(20, input);
     input
         .split("\n\n")
         .map(|tile| {
-            let mut lines = tile.lines();
-            let id = lines
-                .next()
-                .unwrap()
-                .strip_prefix("Tile ")
-                .unwrap()
-                .strip_suffix(":")
-                .unwrap()
-                .parse()
-                .unwrap();
-            let pixels = lines
-                .map(|line| {
-                    line.chars()
-                        .map(|c| c == '#')
-                        .collect_vec()
-                        .try_into()
-                        .unwrap()
-                })
-                .collect_vec()
-                .try_into()
-                .unwrap();
-            Tile { id, pixels }
+            let (header, pixels) = tile.split_once('\n').unwrap_or((tile, ""));
+            let id = ctx.require(
+                header
+                    .strip_prefix("Tile ")
+                    .and_then(|id| id.strip_suffix(':')),
+                header,
+                "`Tile <id>:` header",
+            )?;
+            let id = ctx.parse(id)?;
+            let pixels: Grid<bool> = Grid::parse(&ctx, pixels)?;
+            if (pixels.width(), pixels.height()) != (10, 10) {
+                return Err(ctx.error(tile, "tiles must have 10x10 pixels"));
+            }
+            Ok(Tile { id, pixels })
         })
         .collect()
 }
@@ -96,20 +136,17 @@ fn part1(input: &[Tile]) -> u64 {
     // Store all tile borders
     let mut borders = Map::<_, Set<u64>>::new();
     for tile in input {
-        for &border in &tile.borders() {
+        for border in tile.borders() {
             borders.entry(border).or_default().insert(tile.id);
         }
     }
     // Count the unique borders
     let mut id_count = Map::<_, u32>::new();
-    for (border, ids) in &borders {
+    for ids in borders.values() {
         if ids.len() == 1 {
-            println!("{:?} => {:?}", border, ids);
             *id_count
                 .entry(ids.iter().next().cloned().unwrap())
                 .or_default() += 1;
-        } else if ids.len() > 2 {
-            println!("{:?} => {:?}", border, ids);
         }
     }
     // Count those tiles with 4 unique borders, these are corners
@@ -121,34 +158,144 @@ fn part1(input: &[Tile]) -> u64 {
         .product()
 }
 
-// #[aoc(day20, part2)]
-// fn part2(input: &[Tile]) -> u64 {
-//     todo!()
-// }
+/// Place all tiles in a grid such that all adjacent borders match and stitch the image together
+fn assemble(input: &[Tile]) -> Image {
+    let grid_size = (1..).find(|i| i * i >= input.len()).unwrap();
+
+    // Store all tile borders
+    let mut borders = Map::<_, Set<u64>>::new();
+    for tile in input {
+        for border in tile.borders() {
+            borders.entry(border).or_default().insert(tile.id);
+        }
+    }
+    let is_outer_border = |border: &Vec<bool>| borders[border].len() == 1;
+    let tiles: Map<u64, &Tile> = input.iter().map(|tile| (tile.id, tile)).collect();
+
+    // Any corner works as the top-left tile, as long as its unmatched borders face outwards
+    let corner = input
+        .iter()
+        .find(|tile| {
+            tile.borders()
+                .iter()
+                .filter(|border| is_outer_border(border))
+                .count()
+                == 4
+        })
+        .expect("There must be a corner tile");
+    let corner = corner
+        .orientations()
+        .find(|tile| is_outer_border(&tile.top()) && is_outer_border(&tile.left()))
+        .unwrap();
+
+    // Find the tile, which is not `id` and has the border, in the orientation such that `fits` is true
+    let find_neighbor = |id: u64, border: &[bool], fits: &dyn Fn(&Tile) -> bool| -> Tile {
+        let other_id = borders[border]
+            .iter()
+            .copied()
+            .find(|&other_id| other_id != id)
+            .unwrap_or_else(|| panic!("Tile {} has no neighbor for the border", id));
+        tiles[&other_id]
+            .orientations()
+            .find(|tile| fits(tile))
+            .unwrap_or_else(|| panic!("Tile {} cannot be oriented to fit", other_id))
+    };
+
+    let mut grid: Vec<Vec<Tile>> = Vec::with_capacity(grid_size);
+    for row in 0..grid_size {
+        let mut line: Vec<Tile> = Vec::with_capacity(grid_size);
+        for col in 0..grid_size {
+            let tile = if col > 0 {
+                let left = &line[col - 1];
+                let border = left.right();
+                find_neighbor(left.id, &border, &|tile| tile.left() == border)
+            } else if row > 0 {
+                let above = &grid[row - 1][0];
+                let border = above.bottom();
+                find_neighbor(above.id, &border, &|tile| tile.top() == border)
+            } else {
+                corner.clone()
+            };
+            line.push(tile);
+        }
+        grid.push(line);
+    }
+
+    // Stitch the image together while removing the borders of each tile
+    let pixels = Grid::from_fn(grid_size * 8, grid_size * 8, |(x, y)| {
+        let inner = grid[y / 8][x / 8].pixels.view((1, 1), 8, 8);
+        inner[(x % 8, y % 8)]
+    });
+    Image { pixels }
+}
+
+#[aoc(day20, part2)]
+fn part2(input: &[Tile]) -> usize {
+    assemble(input).water_roughness()
+}
 
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(20899048083289, part1(&values));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day20.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day20.txt").trim()).unwrap();
     assert_eq!(47213728755493, part1(&values));
 }
 
-// #[test]
-// fn test_part2() {
-//     let values = input_generator(PUZZLE);
-//     assert_eq!(46 + 1445 + 669060 + 23340, part2(&values));
-// }
-
-// #[test]
-// fn test_part2_solution() {
-//     let values = input_generator(include_str!("../input/2020/day19.txt").trim());
-//     assert_eq!(88534268715686, part2(&values));
-// }
+#[test]
+fn test_assemble() {
+    let values = input_generator(PUZZLE).unwrap();
+    let image = assemble(&values);
+    let expected: Grid<bool> =
+        Grid::parse(&ParseContext::new(20, PUZZLE_IMAGE), PUZZLE_IMAGE).unwrap();
+    assert!(
+        image.pixels.orientations().any(|pixels| pixels == expected),
+        "Assembled image does not match in any orientation:\n{}",
+        image
+    );
+}
+
+#[test]
+fn test_part2() {
+    let values = input_generator(PUZZLE).unwrap();
+    assert_eq!(273, part2(&values));
+}
+
+#[test]
+fn test_part2_solution() {
+    let values = input_generator(include_str!("../input/2020/day20.txt").trim()).unwrap();
+    assert_eq!(1599, part2(&values));
+}
+
+#[cfg(test)]
+static PUZZLE_IMAGE: &str = r#".#.#..#.##...#.##..#####
+###....#.#....#..#......
+##.##.###.#.#..######...
+###.#####...#.#####.#..#
+##.#....#.##.####...#.##
+...########.#....#####.#
+....#..#...##..#.#.###..
+.####...#..#.....#......
+#..#.##..#..###.#.##....
+#.####..#.####.#.#.###..
+###.#.#...#.######.#..##
+#.####....##..########.#
+##..##.#...#...#.#.#.#..
+...#..#..#.#.##..###.###
+.#.#....#.##.#...###.##.
+###.#...#..#.##.######..
+.#.#.###.##.##.#..#.##..
+.####.###.#...###.#..#.#
+..#.#..#..#.#.#.####.###
+#..####...#.#.#.###.###.
+#####..#####...###....##
+#.##..#..#...#..####...#
+.#.###..##..##..####.##.
+...###...##...#...#..###"#;
 
 #[cfg(test)]
 static PUZZLE: &str = r#"Tile 2311:
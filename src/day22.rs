@@ -2,19 +2,17 @@ use crate::prelude::*;
 use std::collections::VecDeque;
 
 #[aoc_generator(day22)]
-fn input_generator(input: &str) -> (VecDeque<u32>, VecDeque<u32>) {
-    fn parse_deck(i: &str) -> VecDeque<u32> {
-        i.lines()
-            .skip(1)
-            .map(|line| line.parse().unwrap())
-            .collect()
+fn input_generator(input: &str) -> Result<(VecDeque<u32>, VecDeque<u32>), ParseError> {
+    let ctx = ParseContext::new(22, input);
+    let parse_deck = |i: &str| -> Result<VecDeque<u32>, ParseError> {
+        i.lines().skip(1).map(|line| ctx.parse(line)).collect()
     };
 
     let mut parts = input.split("\n\n");
-    (
-        parse_deck(parts.next().unwrap()),
-        parse_deck(parts.next().unwrap()),
-    )
+    Ok((
+        parse_deck(parts.next().unwrap_or_default())?,
+        parse_deck(ctx.require(parts.next(), input, "deck of player 2")?)?,
+    ))
 }
 
 #[aoc(day22, part1)]
@@ -54,7 +52,7 @@ fn score(cards: &VecDeque<u32>) -> usize {
 
 #[aoc(day22, part2)]
 fn part2((player1, player2): &(VecDeque<u32>, VecDeque<u32>)) -> usize {
-    play_recursive_combat(player1.clone(), player2.clone(), 1).1
+    play_recursive_combat(player1.clone(), player2.clone()).1
 }
 
 enum Winner {
@@ -65,16 +63,10 @@ enum Winner {
 fn play_recursive_combat(
     mut player1: VecDeque<u32>,
     mut player2: VecDeque<u32>,
-    game: u32,
 ) -> (Winner, usize) {
     let mut states = Set::<(_, _)>::new();
-    // println!("=== Game {} ===\n", game);
 
     while !player1.is_empty() && !player2.is_empty() {
-        // println!("-- Round {} (Game {}) --", states.len() + 1, game);
-        // println!("Player 1's deck: {:?}", player1);
-        // println!("Player 2's deck: {:?}", player2);
-
         if !states.insert((player1.clone(), player2.clone())) {
             // state already contains this value
             return (Winner::Player1, score(&player1));
@@ -82,14 +74,11 @@ fn play_recursive_combat(
 
         let c1 = player1.pop_front().unwrap();
         let c2 = player2.pop_front().unwrap();
-        // println!("Player 1 plays: {}", c1);
-        // println!("Player 2 plays: {}", c2);
 
         let winner = if player1.len() >= c1 as _ && player2.len() >= c2 as _ {
             play_recursive_combat(
                 player1.iter().take(c1 as _).copied().collect(),
                 player2.iter().take(c2 as _).copied().collect(),
-                game + 1,
             )
             .0
         } else if c1 > c2 {
@@ -100,12 +89,10 @@ fn play_recursive_combat(
 
         match winner {
             Winner::Player1 => {
-                // println!("Player 1 wins round {} of game {}!\n", states.len(), game);
                 player1.push_back(c1);
                 player1.push_back(c2);
             }
             Winner::Player2 => {
-                // println!("Player 2 wins round {} of game {}!\n", states.len(), game);
                 player2.push_back(c2);
                 player2.push_back(c1);
             }
@@ -121,25 +108,25 @@ fn play_recursive_combat(
 
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(306, part1(&values));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day22.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day22.txt").trim()).unwrap();
     assert_eq!(34566, part1(&values));
 }
 
 #[test]
 fn test_part2() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!(291, part2(&values));
 }
 
 #[test]
 fn test_part2_solution() {
-    let values = input_generator(include_str!("../input/2020/day22.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day22.txt").trim()).unwrap();
     assert_eq!(31854, part2(&values));
 }
 
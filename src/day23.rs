@@ -1,5 +1,4 @@
 use crate::prelude::*;
-use std::fmt::Display;
 
 #[derive(Clone)]
 struct Cups {
@@ -44,7 +43,7 @@ impl Cups {
             .collect()
     }
 
-    fn destination(&mut self) -> (usize, u8) {
+    fn destination(&mut self) -> usize {
         let mut cursor_value = self.values[self.cursor];
         loop {
             cursor_value = cursor_value
@@ -52,9 +51,7 @@ impl Cups {
                 .unwrap_or_else(|| self.values.iter().copied().max().unwrap());
             match self.values.iter().find_position(|&&v| v == cursor_value) {
                 None => continue,
-                Some((position, &destination)) => {
-                    return (position, destination);
-                }
+                Some((position, _)) => return position,
             }
         }
     }
@@ -64,28 +61,102 @@ impl Cups {
     }
 }
 
-impl Display for Cups {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for (i, v) in self.values.iter().enumerate() {
-            if i > 0 {
-                write!(f, " ")?;
-            }
-            if i == self.cursor {
-                write!(f, "(")?;
-            }
-            v.fmt(f)?;
-            if i == self.cursor {
-                write!(f, ")")?;
+/// Cups stored as a successor array, which allows constant time moves independent of the number of cups
+///
+/// `next[label]` is the label of the cup clockwise of the cup `label`.
+/// Index 0 is unused, since labels start at 1.
+struct CupCircle {
+    current: u32,
+    next: Vec<u32>,
+}
+
+impl CupCircle {
+    fn new(labels: impl IntoIterator<Item = u32>) -> Self {
+        let labels = labels.into_iter().collect_vec();
+        let max = labels.iter().copied().max().unwrap_or(0);
+        let mut next = vec![0; max as usize + 1];
+        for (&label, &next_label) in labels.iter().zip(labels.iter().cycle().skip(1)) {
+            next[label as usize] = next_label;
+        }
+        CupCircle {
+            current: labels[0],
+            next,
+        }
+    }
+
+    fn max_label(&self) -> u32 {
+        (self.next.len() - 1) as u32
+    }
+
+    fn play(&mut self, moves: usize) {
+        for _ in 0..moves {
+            let a = self.next[self.current as usize];
+            let b = self.next[a as usize];
+            let c = self.next[b as usize];
+            // Remove the three picked up cups from the circle
+            self.next[self.current as usize] = self.next[c as usize];
+
+            let mut destination = self.current;
+            loop {
+                destination = if destination == 1 {
+                    self.max_label()
+                } else {
+                    destination - 1
+                };
+                if destination != a && destination != b && destination != c {
+                    break;
+                }
             }
+
+            // Insert the picked up cups after the destination
+            self.next[c as usize] = self.next[destination as usize];
+            self.next[destination as usize] = a;
+            self.current = self.next[self.current as usize];
         }
-        Ok(())
+    }
+
+    /// Iterate over all cup labels clockwise after cup `label`, excluding `label` itself
+    fn after(&self, label: u32) -> impl Iterator<Item = u32> + '_ {
+        let mut curr = label;
+        std::iter::from_fn(move || {
+            curr = self.next[curr as usize];
+            if curr == label {
+                None
+            } else {
+                Some(curr)
+            }
+        })
     }
 }
 
 #[aoc_generator(day23)]
-fn input_generator(input: &str) -> Cups {
-    let values = input.chars().map(|c| c as u8 - b'0').collect();
-    Cups { cursor: 0, values }
+fn input_generator(input: &str) -> Result<Cups, ParseError> {
+    let ctx = ParseContext::new(23, input);
+    let values: Vec<u8> = input
+        .char_indices()
+        .map(|(idx, c)| match c.to_digit(10) {
+            Some(d) if d > 0 => Ok(d as u8),
+            _ => Err(ctx.error(
+                &input[idx..idx + c.len_utf8()],
+                "cup labels must be 1-9, got",
+            )),
+        })
+        .collect::<Result<_, _>>()?;
+    if values.is_empty() {
+        return Err(ctx.error(input, "missing cup labels in"));
+    }
+    // The labels must be a permutation of 1..=n, all labels are a single ASCII digit
+    let mut seen = Set::new();
+    for (idx, &v) in values.iter().enumerate() {
+        let label = &input[idx..idx + 1];
+        if usize::from(v) > values.len() {
+            return Err(ctx.error(label, format!("cup labels must be 1-{}, got", values.len())));
+        }
+        if !seen.insert(v) {
+            return Err(ctx.error(label, "duplicate cup label"));
+        }
+    }
+    Ok(Cups { cursor: 0, values })
 }
 
 #[aoc(day23, part1)]
@@ -93,53 +164,88 @@ fn part1(cups: &Cups) -> String {
     run(100, cups.clone())
 }
 
-fn run(moves: u8, mut cups: Cups) -> String {
-    for m in 0..moves {
-        println!("-- move {} --\ncups: {}", m + 1, cups);
+fn run(moves: u32, mut cups: Cups) -> String {
+    for _ in 0..moves {
         let pick_up = cups.pick_up();
-        println!("pick up: {}, {}, {}", pick_up[0], pick_up[1], pick_up[2]);
-        let (pos, dest) = cups.destination();
-        println!("destination: {}\n", dest);
+        let pos = cups.destination();
         cups.insert(pos, pick_up);
         cups.advance();
     }
     cups.cups_order()
 }
 
-// #[aoc(day23, part2)]
-// fn part2((player1, player2): &(VecDeque<u32>, VecDeque<u32>)) -> usize {
-//     todo!()
-// }
+#[aoc(day23, part2)]
+fn part2(cups: &Cups) -> u64 {
+    let labels = cups.values.iter().map(|&v| u32::from(v));
+    let max = labels.clone().max().unwrap();
+    let mut circle = CupCircle::new(labels.chain((max + 1)..=1_000_000));
+    circle.play(10_000_000);
+    circle.after(1).take(2).map(u64::from).product()
+}
 
 #[test]
 fn test_part1_small() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!("92658374", run(10, values));
 }
 
 #[test]
 fn test_part1() {
-    let values = input_generator(PUZZLE);
+    let values = input_generator(PUZZLE).unwrap();
     assert_eq!("67384529", part1(&values));
 }
 
 #[test]
 fn test_part1_solution() {
-    let values = input_generator(include_str!("../input/2020/day23.txt").trim());
+    let values = input_generator(include_str!("../input/2020/day23.txt").trim()).unwrap();
     assert_eq!("69473825", part1(&values));
 }
 
-// #[test]
-// fn test_part2() {
-//     let values = input_generator(PUZZLE);
-//     assert_eq!(291, part2(&values));
-// }
+#[test]
+fn test_cup_circle() {
+    let values = input_generator(PUZZLE).unwrap();
+    let mut circle = CupCircle::new(values.values.iter().map(|&v| u32::from(v)));
+    circle.play(10);
+    assert_eq!("92658374", circle.after(1).join(""));
+    circle.play(90);
+    assert_eq!("67384529", circle.after(1).join(""));
+}
+
+#[test]
+fn test_empty_input() {
+    assert_eq!(
+        "day23 line 1 column 1: missing cup labels in ''",
+        input_generator("").err().unwrap().to_string()
+    );
+}
+
+#[test]
+fn test_duplicate_labels() {
+    assert_eq!(
+        "day23 line 1 column 4: duplicate cup label '3'",
+        input_generator("3213").err().unwrap().to_string()
+    );
+}
+
+#[test]
+fn test_label_out_of_range() {
+    assert_eq!(
+        "day23 line 1 column 4: cup labels must be 1-4, got '5'",
+        input_generator("1245").err().unwrap().to_string()
+    );
+}
 
-// #[test]
-// fn test_part2_solution() {
-//     let values = input_generator(include_str!("../input/2020/day23.txt").trim());
-//     assert_eq!(31854, part2(&values));
-// }
+#[test]
+fn test_part2() {
+    let values = input_generator(PUZZLE).unwrap();
+    assert_eq!(149245887792, part2(&values));
+}
+
+#[test]
+fn test_part2_solution() {
+    let values = input_generator(include_str!("../input/2020/day23.txt").trim()).unwrap();
+    assert_eq!(96604396189, part2(&values));
+}
 
 #[cfg(test)]
 static PUZZLE: &str = r#"389125467"#;
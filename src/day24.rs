@@ -0,0 +1,186 @@
+use crate::grid::{Grid, Neighborhood};
+use crate::prelude::*;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Direction {
+    East,
+    SouthEast,
+    SouthWest,
+    West,
+    NorthWest,
+    NorthEast,
+}
+
+impl Direction {
+    /// Offset in axial hex coordinates `(q, r)`
+    fn vector(&self) -> (i32, i32) {
+        match self {
+            Direction::East => (1, 0),
+            Direction::SouthEast => (0, 1),
+            Direction::SouthWest => (-1, 1),
+            Direction::West => (-1, 0),
+            Direction::NorthWest => (0, -1),
+            Direction::NorthEast => (1, -1),
+        }
+    }
+}
+
+#[aoc_generator(day24)]
+fn input_generator(input: &str) -> Result<Vec<Vec<Direction>>, ParseError> {
+    let ctx = ParseContext::new(24, input);
+    input
+        .lines()
+        .map(|line| {
+            let mut rest = line;
+            let mut directions = Vec::new();
+            while !rest.is_empty() {
+                let (direction, len) = if rest.starts_with('e') {
+                    (Direction::East, 1)
+                } else if rest.starts_with('w') {
+                    (Direction::West, 1)
+                } else if rest.starts_with("se") {
+                    (Direction::SouthEast, 2)
+                } else if rest.starts_with("sw") {
+                    (Direction::SouthWest, 2)
+                } else if rest.starts_with("nw") {
+                    (Direction::NorthWest, 2)
+                } else if rest.starts_with("ne") {
+                    (Direction::NorthEast, 2)
+                } else {
+                    let len = rest.chars().take(2).map(char::len_utf8).sum();
+                    return Err(ctx.error(&rest[..len], "unknown direction"));
+                };
+                directions.push(direction);
+                rest = &rest[len..];
+            }
+            Ok(directions)
+        })
+        .collect()
+}
+
+/// Flip all tiles described by the input and return the set of black tiles
+fn black_tiles(input: &[Vec<Direction>]) -> Set<(i32, i32)> {
+    let mut black = Set::new();
+    for directions in input {
+        let tile = directions.iter().fold((0, 0), |(q, r), dir| {
+            let (dq, dr) = dir.vector();
+            (q + dq, r + dr)
+        });
+        if !black.remove(&tile) {
+            black.insert(tile);
+        }
+    }
+    black
+}
+
+/// Store the black tiles in a grid of axial coordinates, large enough to grow for `days` days
+fn floor(black: &Set<(i32, i32)>, days: usize) -> Grid<bool> {
+    let pad = days as i32 + 1;
+    let min_q = black.iter().map(|&(q, _)| q).min().unwrap_or(0) - pad;
+    let max_q = black.iter().map(|&(q, _)| q).max().unwrap_or(0) + pad;
+    let min_r = black.iter().map(|&(_, r)| r).min().unwrap_or(0) - pad;
+    let max_r = black.iter().map(|&(_, r)| r).max().unwrap_or(0) + pad;
+    Grid::from_fn(
+        (max_q - min_q + 1) as usize,
+        (max_r - min_r + 1) as usize,
+        |(x, y)| black.contains(&(x as i32 + min_q, y as i32 + min_r)),
+    )
+}
+
+/// Advance the hex grid by one day
+fn next_day(floor: &Grid<bool>) -> Grid<bool> {
+    Grid::from_fn(floor.width(), floor.height(), |pos| {
+        let count = floor
+            .neighbors(pos, Neighborhood::Hex)
+            .filter(|&pos| floor[pos])
+            .count();
+        matches!((floor[pos], count), (true, 1) | (true, 2) | (false, 2))
+    })
+}
+
+#[aoc(day24, part1)]
+fn part1(input: &[Vec<Direction>]) -> usize {
+    black_tiles(input).len()
+}
+
+#[aoc(day24, part2)]
+fn part2(input: &[Vec<Direction>]) -> usize {
+    let mut floor = floor(&black_tiles(input), 100);
+    for _ in 0..100 {
+        floor = next_day(&floor);
+    }
+    floor.iter().filter(|&&black| black).count()
+}
+
+#[test]
+fn test_parse() {
+    use Direction::*;
+    assert_eq!(
+        vec![vec![East, SouthEast, NorthEast, East]],
+        input_generator("esenee").unwrap()
+    );
+    assert_eq!(1, black_tiles(&input_generator("nwwswee").unwrap()).len());
+    assert!(black_tiles(&input_generator("nwwswee").unwrap()).contains(&(0, 0)));
+}
+
+#[test]
+fn test_direction_vectors() {
+    // Flipping tiles and counting neighbors must agree on the adjacent tiles
+    use Direction::*;
+    let vectors: Set<_> = [East, SouthEast, SouthWest, West, NorthWest, NorthEast]
+        .iter()
+        .map(|dir| dir.vector())
+        .collect();
+    let offsets: Set<_> = Neighborhood::Hex
+        .offsets()
+        .iter()
+        .map(|&(dq, dr)| (dq as i32, dr as i32))
+        .collect();
+    assert_eq!(6, vectors.len());
+    assert_eq!(offsets, vectors);
+}
+
+#[test]
+fn test_part1() {
+    let values = input_generator(PUZZLE).unwrap();
+    assert_eq!(10, part1(&values));
+}
+
+#[test]
+fn test_next_day() {
+    let values = input_generator(PUZZLE).unwrap();
+    let expected = [15, 12, 25, 14, 23, 28, 41, 37, 49, 37];
+    let mut floor = floor(&black_tiles(&values), expected.len());
+    for &count in &expected {
+        floor = next_day(&floor);
+        assert_eq!(count, floor.iter().filter(|&&black| black).count());
+    }
+}
+
+#[test]
+fn test_part2() {
+    let values = input_generator(PUZZLE).unwrap();
+    assert_eq!(2208, part2(&values));
+}
+
+#[cfg(test)]
+static PUZZLE: &str = r#"sesenwnenenewseeswwswswwnenewsewsw
+neeenesenwnwwswnenewnwwsewnenwseswesw
+seswneswswsenwwnwse
+nwnwneseeswswnenewneswwnewseswneseene
+swweswneswnenwsewnwneneseenw
+eesenwseswswnenwswnwnwsewwnwsene
+sewnenenenesenwsewnenwwwse
+wenwwweseeeweswwwnwwe
+wsweesenenewnwwnwsenewsenwwsesesenwne
+neeswseenwwswnwswswnw
+nenwswwsewswnenenewsenwsenwnesesenew
+enewnwewneswsewnwswenweswnenwsenwsw
+sweneswneswneneenwnewenewwneswswnese
+swwesenesewenwneswnwwneseswwne
+enesenwswwswneneswsenwnewswseenwsese
+wnwnesenesenenwwnenwsewesewsesesew
+nenewswnwewswnenesenwnesewesw
+eneswnwswnwsenenwnwnwwseeswneewsenese
+neswnwewnwnwseenwseesewsenwsweewe
+wseweeenwnesenwwwswnew"#;
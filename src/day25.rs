@@ -0,0 +1,60 @@
+use crate::modular::{discrete_log, mul_mod, pow_mod};
+use crate::prelude::*;
+
+const SUBJECT_NUMBER: u64 = 7;
+const MODULUS: u64 = 20201227;
+
+#[aoc_generator(day25)]
+fn input_generator(input: &str) -> Result<(u64, u64), ParseError> {
+    let ctx = ParseContext::new(25, input);
+    let mut lines = input.lines();
+    let card = ctx.parse(lines.next().unwrap_or_default())?;
+    let door = ctx.parse(ctx.require(lines.next(), input, "door public key")?)?;
+    Ok((card, door))
+}
+
+/// Find the loop size by repeatedly transforming the subject number until the public key appears
+fn loop_size_naive(public_key: u64) -> u64 {
+    let mut value = 1;
+    let mut loop_size = 0;
+    while value != public_key {
+        value = mul_mod(value, SUBJECT_NUMBER, MODULUS);
+        loop_size += 1;
+    }
+    loop_size
+}
+
+#[aoc(day25, part1)]
+fn part1(&(card, door): &(u64, u64)) -> u64 {
+    let card_loop_size = loop_size_naive(card);
+    pow_mod(door, card_loop_size, MODULUS)
+}
+
+#[aoc(day25, part1, bsgs)]
+fn part1_bsgs(&(card, door): &(u64, u64)) -> u64 {
+    let card_loop_size =
+        discrete_log(SUBJECT_NUMBER, card, MODULUS).expect("Card public key has no loop size");
+    pow_mod(door, card_loop_size, MODULUS)
+}
+
+#[test]
+fn test_loop_size() {
+    assert_eq!(8, loop_size_naive(5764801));
+    assert_eq!(11, loop_size_naive(17807724));
+}
+
+#[test]
+fn test_part1() {
+    let values = input_generator(PUZZLE).unwrap();
+    assert_eq!(14897079, part1(&values));
+}
+
+#[test]
+fn test_part1_bsgs() {
+    let values = input_generator(PUZZLE).unwrap();
+    assert_eq!(14897079, part1_bsgs(&values));
+}
+
+#[cfg(test)]
+static PUZZLE: &str = r#"5764801
+17807724"#;
@@ -0,0 +1,68 @@
+//! Minimal writer for Graphviz DOT graphs
+//!
+//! Nodes and edges are written in the order they are added, so callers control the output order.
+
+use std::fmt::Write;
+
+/// A directed graph, which is built node by node and edge by edge
+pub struct Dot {
+    out: String,
+}
+
+impl Dot {
+    pub fn new(name: &str) -> Self {
+        let mut out = String::new();
+        writeln!(out, "digraph {} {{", quote(name)).unwrap();
+        Dot { out }
+    }
+
+    pub fn node(&mut self, id: &str, label: &str, highlight: bool) {
+        write!(self.out, "    {} [label={}", quote(id), quote(label)).unwrap();
+        if highlight {
+            self.out.push_str(", color=red, fontcolor=red");
+        }
+        self.out.push_str("];\n");
+    }
+
+    pub fn edge(&mut self, from: &str, to: &str, label: &str, highlight: bool) {
+        write!(
+            self.out,
+            "    {} -> {} [label={}",
+            quote(from),
+            quote(to),
+            quote(label)
+        )
+        .unwrap();
+        if highlight {
+            self.out.push_str(", color=red, fontcolor=red");
+        }
+        self.out.push_str("];\n");
+    }
+
+    pub fn finish(mut self) -> String {
+        self.out.push_str("}\n");
+        self.out
+    }
+}
+
+/// Quote a DOT identifier
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[test]
+fn test_dot() {
+    let mut dot = Dot::new("test");
+    dot.node("a", "say \"hi\"", true);
+    dot.node("b", "b", false);
+    dot.edge("a", "b", "1", true);
+    assert_eq!(
+        r#"digraph "test" {
+    "a" [label="say \"hi\"", color=red, fontcolor=red];
+    "b" [label="b"];
+    "a" -> "b" [label="1", color=red, fontcolor=red];
+}
+"#,
+        dot.finish()
+    );
+}
@@ -0,0 +1,382 @@
+//! A dense 2D grid shared by all days working with char maps
+//!
+//! Positions are `(x, y)` pairs, where `x` is the column and `y` is the row, starting in the top-left corner.
+
+use crate::prelude::*;
+use std::fmt::{self, Display};
+use std::ops::{Index, IndexMut};
+
+/// A value which can be stored in a [`Grid`] and be converted from and to the char of the puzzle input
+pub trait GridCell: Sized {
+    fn from_char(c: char) -> Option<Self>;
+    fn to_char(&self) -> char;
+}
+
+impl GridCell for bool {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        }
+    }
+
+    fn to_char(&self) -> char {
+        if *self {
+            '#'
+        } else {
+            '.'
+        }
+    }
+}
+
+impl GridCell for char {
+    fn from_char(c: char) -> Option<Self> {
+        Some(c)
+    }
+
+    fn to_char(&self) -> char {
+        *self
+    }
+}
+
+/// Which cells count as the neighbors of a cell
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Neighborhood {
+    /// The horizontally and vertically adjacent cells
+    Four,
+    /// All horizontally, vertically, and diagonally adjacent cells
+    Eight,
+    /// The six neighbors of a hex grid stored in axial coordinates
+    Hex,
+}
+
+impl Neighborhood {
+    pub fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Neighborhood::Four => &[(0, -1), (-1, 0), (1, 0), (0, 1)],
+            Neighborhood::Eight => &[
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ],
+            Neighborhood::Hex => &[(1, 0), (0, 1), (-1, 1), (-1, 0), (0, -1), (1, -1)],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    /// All cells in row-major order
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn from_fn(width: usize, height: usize, f: impl FnMut((usize, usize)) -> T) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(f)
+            .collect();
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parse a char map, in which every line is one row of the grid
+    pub fn parse(ctx: &ParseContext<'_>, input: &str) -> Result<Self, ParseError>
+    where
+        T: GridCell,
+    {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::with_capacity(input.len());
+        for line in input.lines() {
+            for (idx, c) in line.char_indices() {
+                let cell = T::from_char(c).ok_or_else(|| {
+                    ctx.error(&line[idx..idx + c.len_utf8()], "unknown map symbol")
+                })?;
+                cells.push(cell);
+            }
+            let line_width = line.chars().count();
+            if *width.get_or_insert(line_width) != line_width {
+                return Err(ctx.error(line, "all rows must have the same width"));
+            }
+            height += 1;
+        }
+        Ok(Grid {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, (x, y): (usize, usize)) -> Option<&T> {
+        if x < self.width && y < self.height {
+            self.cells.get(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, (x, y): (usize, usize)) -> Option<&mut T> {
+        if x < self.width && y < self.height {
+            self.cells.get_mut(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// Move from `pos` by `offset`, returning `None` if this leaves the grid
+    pub fn offset(
+        &self,
+        (x, y): (usize, usize),
+        (dx, dy): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        let x = (x as isize).checked_add(dx)?;
+        let y = (y as isize).checked_add(dy)?;
+        if 0 <= x && (x as usize) < self.width && 0 <= y && (y as usize) < self.height {
+            Some((x as usize, y as usize))
+        } else {
+            None
+        }
+    }
+
+    /// Iterate over all positions in row-major order
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Iterate over all cells in row-major order
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    pub fn rows(&self) -> std::slice::Chunks<'_, T> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..][..self.width]
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    /// Iterate over all neighbor positions of `pos` which are inside the grid
+    pub fn neighbors(
+        &self,
+        pos: (usize, usize),
+        neighborhood: Neighborhood,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        neighborhood
+            .offsets()
+            .iter()
+            .filter_map(move |&offset| self.offset(pos, offset))
+    }
+
+    /// Walk from `pos` in `direction` and return the first position which is not skipped
+    ///
+    /// Returns `None` if the ray leaves the grid before that.
+    pub fn ray_cast(
+        &self,
+        mut pos: (usize, usize),
+        direction: (isize, isize),
+        mut skip: impl FnMut(&T) -> bool,
+    ) -> Option<(usize, usize)> {
+        loop {
+            pos = self.offset(pos, direction)?;
+            if !skip(&self[pos]) {
+                return Some(pos);
+            }
+        }
+    }
+
+    /// A borrowed rectangular part of the grid, starting at `(x, y)`
+    pub fn view(&self, (x, y): (usize, usize), width: usize, height: usize) -> GridView<'_, T> {
+        assert!(
+            x + width <= self.width && y + height <= self.height,
+            "View must be inside the grid"
+        );
+        GridView {
+            grid: self,
+            origin: (x, y),
+            width,
+            height,
+        }
+    }
+
+    /// Rotate the grid clockwise by 90°
+    pub fn rotate_right(&self) -> Self
+    where
+        T: Clone,
+    {
+        Grid::from_fn(self.height, self.width, |(x, y)| {
+            self[(y, self.height - 1 - x)].clone()
+        })
+    }
+
+    /// Mirror the grid along the vertical axis
+    pub fn flip_horizontal(&self) -> Self
+    where
+        T: Clone,
+    {
+        Grid::from_fn(self.width, self.height, |(x, y)| {
+            self[(self.width - 1 - x, y)].clone()
+        })
+    }
+
+    /// All 8 combinations of rotations and flips
+    ///
+    /// The first four are the rotations by 0°, 90°, 180°, and 270°, the last four the same for the flipped grid.
+    pub fn orientations(&self) -> impl Iterator<Item = Self>
+    where
+        T: Clone,
+    {
+        let flipped = self.flip_horizontal();
+        let rotations = |grid: Self| {
+            std::iter::successors(Some(grid), |grid| Some(grid.rotate_right())).take(4)
+        };
+        rotations(self.clone()).chain(rotations(flipped))
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: (usize, usize)) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("Position {:?} is outside of the grid", pos))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, pos: (usize, usize)) -> &mut T {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("Position {:?} is outside of the grid", pos))
+    }
+}
+
+impl<T: GridCell> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell.to_char())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A borrowed rectangular part of a [`Grid`], created by [`Grid::view`]
+#[derive(Copy, Clone, Debug)]
+pub struct GridView<'a, T> {
+    grid: &'a Grid<T>,
+    origin: (usize, usize),
+    width: usize,
+    height: usize,
+}
+
+impl<'a, T> GridView<'a, T> {
+    pub fn get(&self, (x, y): (usize, usize)) -> Option<&'a T> {
+        if x < self.width && y < self.height {
+            self.grid.get((self.origin.0 + x, self.origin.1 + y))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> Index<(usize, usize)> for GridView<'a, T> {
+    type Output = T;
+
+    fn index(&self, pos: (usize, usize)) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("Position {:?} is outside of the view", pos))
+    }
+}
+
+#[cfg(test)]
+fn parse_grid(input: &str) -> Grid<char> {
+    Grid::parse(&ParseContext::new(0, input), input).unwrap()
+}
+
+#[test]
+fn test_parse_and_display() {
+    let grid = parse_grid("ab\ncd\nef");
+    assert_eq!((2, 3), (grid.width(), grid.height()));
+    assert_eq!('d', grid[(1, 1)]);
+    assert_eq!("ab\ncd\nef\n", grid.to_string());
+
+    let err = Grid::<bool>::parse(&ParseContext::new(0, "#.\n#x"), "#.\n#x").unwrap_err();
+    assert_eq!((2, 2), (err.line, err.column));
+    assert!(Grid::<bool>::parse(&ParseContext::new(0, "#.\n#"), "#.\n#").is_err());
+}
+
+#[test]
+fn test_rotate_and_flip() {
+    let grid = parse_grid("abc\ndef");
+    assert_eq!("da\neb\nfc\n", grid.rotate_right().to_string());
+    assert_eq!("cba\nfed\n", grid.flip_horizontal().to_string());
+    assert_eq!(
+        grid,
+        grid.rotate_right()
+            .rotate_right()
+            .rotate_right()
+            .rotate_right()
+    );
+
+    let orientations: Set<String> = grid.orientations().map(|g| g.to_string()).collect();
+    assert_eq!(8, orientations.len());
+}
+
+#[test]
+fn test_neighbors() {
+    let grid = parse_grid("abc\ndef\nghi");
+    let neighbors = |pos, n| {
+        grid.neighbors(pos, n)
+            .map(|pos| grid[pos])
+            .collect::<String>()
+    };
+    assert_eq!("bdfh", neighbors((1, 1), Neighborhood::Four));
+    assert_eq!("bd", neighbors((0, 0), Neighborhood::Four));
+    assert_eq!("abcdfghi", neighbors((1, 1), Neighborhood::Eight));
+    assert_eq!("bde", neighbors((0, 0), Neighborhood::Eight));
+    assert_eq!("fhgdbc", neighbors((1, 1), Neighborhood::Hex));
+}
+
+#[test]
+fn test_ray_cast() {
+    let grid = parse_grid("a..b\n....\n...c");
+    assert_eq!(Some((3, 0)), grid.ray_cast((0, 0), (1, 0), |&c| c == '.'));
+    assert_eq!(None, grid.ray_cast((0, 0), (0, 1), |&c| c == '.'));
+    assert_eq!(Some((3, 2)), grid.ray_cast((1, 0), (1, 1), |&c| c == '.'));
+}
+
+#[test]
+fn test_view() {
+    let grid = parse_grid("abcd\nefgh\nijkl");
+    let view = grid.view((1, 1), 2, 2);
+    assert_eq!('f', view[(0, 0)]);
+    assert_eq!('k', view[(1, 1)]);
+    assert_eq!(None, view.get((2, 0)));
+}
@@ -1,31 +1,44 @@
 #[macro_use]
 extern crate aoc_runner_derive;
 
+mod adapters;
+mod animation;
+mod assignment;
+mod dot;
+mod grid;
+mod modular;
+mod parse;
 mod prelude;
+mod svg;
+mod visualize;
 
-// mod day01;
-// mod day02;
-// mod day03;
-// mod day04;
-// mod day05;
-// mod day06;
-// mod day07;
-// mod day08;
-// mod day09;
-// mod day10;
-// mod day11;
-// mod day12;
-// mod day13;
-// mod day14;
-// mod day15;
-// mod day16;
-// mod day17;
-// mod day18;
-// mod day19;
+mod day01;
+mod day02;
+mod day03;
+mod day04;
+mod day05;
+mod day06;
+mod day07;
+mod day08;
+mod day09;
+mod day10;
+mod day11;
+mod day12;
+mod day13;
+mod day14;
+mod day15;
+mod day16;
+mod day17;
+mod day18;
+mod day19;
 mod day20;
 mod day21;
 mod day22;
 mod day23;
-// mod day24;
+mod day24;
+mod day25;
+
+pub use grid::{Grid, GridCell, GridView, Neighborhood};
+pub use visualize::export as visualize;
 
 aoc_runner_derive::aoc_lib! { year = 2020 }
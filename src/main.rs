@@ -1 +0,0 @@
-aoc_runner_derive::aoc_main! { lib = advent_of_code_2020 }
@@ -0,0 +1,217 @@
+//! Helpers for modular arithmetic
+
+use crate::prelude::*;
+
+/// Calculate `(a * b) % modulus` without overflowing
+pub fn mul_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    (u128::from(a) * u128::from(b) % u128::from(modulus)) as u64
+}
+
+/// Calculate `base.pow(exp) % modulus` using square-and-multiply
+pub fn pow_mod(mut base: u64, mut exp: u64, modulus: u64) -> u64 {
+    let mut res = 1 % modulus;
+    base %= modulus;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            res = mul_mod(res, base, modulus);
+        }
+        base = mul_mod(base, base, modulus);
+        exp >>= 1;
+    }
+    res
+}
+
+/// Calculate `(a * b) % modulus` for `modulus < 2^127` without overflowing, using double-and-add
+fn mul_mod_u128(mut a: u128, mut b: u128, modulus: u128) -> u128 {
+    debug_assert!(modulus <= i128::MAX as u128);
+    a %= modulus;
+    let mut res = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            res = (res + a) % modulus;
+        }
+        a = (a + a) % modulus;
+        b >>= 1;
+    }
+    res
+}
+
+/// Extended Euclidean algorithm
+///
+/// Returns `(g, x, y)` such that `g = gcd(a, b) = a * x + b * y`.
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+/// Calculate the multiplicative inverse of `a` modulo `modulus`, if it exists
+pub fn inverse_mod(a: u64, modulus: u64) -> Option<u64> {
+    let (g, x, _) = extended_gcd(i128::from(a), i128::from(modulus));
+    if g != 1 {
+        return None;
+    }
+    Some(x.rem_euclid(i128::from(modulus)) as u64)
+}
+
+/// Find the smallest `x` such that `base.pow(x) % modulus == target` using baby-step giant-step
+///
+/// Runs in `O(sqrt(modulus))` time and space.
+/// Returns `None` if no such `x` exists or `base` is not invertible modulo `modulus`.
+pub fn discrete_log(base: u64, target: u64, modulus: u64) -> Option<u64> {
+    let step = (1..).find(|i| i * i >= modulus).unwrap();
+
+    // Baby steps: base^j for all j < step
+    let mut baby_steps = HashMap::with_capacity(step as usize);
+    let mut value = 1 % modulus;
+    for j in 0..step {
+        baby_steps.entry(value).or_insert(j);
+        value = mul_mod(value, base, modulus);
+    }
+
+    // Giant steps: target * base^(-step * i)
+    let giant_step = pow_mod(inverse_mod(base, modulus)?, step, modulus);
+    let mut value = target % modulus;
+    for i in 0..step {
+        if let Some(&j) = baby_steps.get(&value) {
+            return Some(i * step + j);
+        }
+        value = mul_mod(value, giant_step, modulus);
+    }
+    None
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CrtError {
+    /// The congruence at this index contradicts the previous ones
+    NoSolution { index: usize },
+    /// The modulus of the congruence at this index is zero
+    ZeroModulus { index: usize },
+    /// The combined modulus does not fit into `u128`
+    Overflow,
+}
+
+impl std::fmt::Display for CrtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CrtError::NoSolution { index } => write!(
+                f,
+                "congruence {} contradicts the previous congruences",
+                index
+            ),
+            CrtError::ZeroModulus { index } => write!(f, "congruence {} has modulus zero", index),
+            CrtError::Overflow => write!(f, "combined modulus overflows"),
+        }
+    }
+}
+
+impl std::error::Error for CrtError {}
+
+/// Solve a system of congruences `x ≡ residue (mod modulus)` with the Chinese remainder theorem
+///
+/// The moduli do not need to be coprime.
+/// Returns the smallest non-negative solution `x` and the least common multiple of all moduli, which is the period of all solutions.
+pub fn crt(congruences: &[(u128, u128)]) -> Result<(u128, u128), CrtError> {
+    let (mut x, mut modulus) = (0_u128, 1_u128);
+    for (index, &(residue, other_modulus)) in congruences.iter().enumerate() {
+        if other_modulus == 0 {
+            return Err(CrtError::ZeroModulus { index });
+        }
+        let residue = residue % other_modulus;
+        if other_modulus > i128::MAX as u128 || modulus > i128::MAX as u128 {
+            return Err(CrtError::Overflow);
+        }
+
+        // Find `k` with `x + modulus * k ≡ residue (mod other_modulus)`
+        let (g, inverse, _) = extended_gcd(modulus as i128, other_modulus as i128);
+        let g = g as u128;
+        let diff = (residue + other_modulus - x % other_modulus) % other_modulus;
+        if diff % g != 0 {
+            return Err(CrtError::NoSolution { index });
+        }
+        let reduced_modulus = other_modulus / g;
+        // `modulus / g` is invertible modulo `reduced_modulus`, with `inverse` as inverse
+        let inverse = inverse.rem_euclid(reduced_modulus as i128) as u128;
+        let k = mul_mod_u128((diff / g) % reduced_modulus, inverse, reduced_modulus);
+
+        let lcm = modulus
+            .checked_mul(reduced_modulus)
+            .ok_or(CrtError::Overflow)?;
+        // `x < modulus` and `k < reduced_modulus`, thus the sum is below `lcm`
+        x += modulus * k;
+        modulus = lcm;
+    }
+    Ok((x, modulus))
+}
+
+#[test]
+fn test_pow_mod() {
+    assert_eq!(1, pow_mod(7, 0, 20201227));
+    assert_eq!(5764801, pow_mod(7, 8, 20201227));
+    assert_eq!(17807724, pow_mod(7, 11, 20201227));
+    assert_eq!(0, pow_mod(3, 5, 1));
+}
+
+#[test]
+fn test_inverse_mod() {
+    assert_eq!(Some(4), inverse_mod(3, 11));
+    assert_eq!(None, inverse_mod(4, 8));
+}
+
+#[test]
+fn test_discrete_log() {
+    assert_eq!(Some(8), discrete_log(7, 5764801, 20201227));
+    assert_eq!(Some(11), discrete_log(7, 17807724, 20201227));
+    assert_eq!(Some(0), discrete_log(7, 1, 20201227));
+    // 2 only generates {1, 2, 4} modulo 7
+    assert_eq!(None, discrete_log(2, 3, 7));
+}
+
+#[test]
+fn test_crt() {
+    assert_eq!(Ok((0, 1)), crt(&[]));
+    assert_eq!(Ok((23, 105)), crt(&[(2, 3), (3, 5), (2, 7)]));
+    // Non-coprime moduli
+    assert_eq!(Ok((10, 12)), crt(&[(2, 4), (4, 6)]));
+    assert_eq!(Ok((3, 6)), crt(&[(3, 6), (1, 2), (0, 3)]));
+    assert_eq!(
+        Err(CrtError::NoSolution { index: 1 }),
+        crt(&[(1, 4), (2, 6)])
+    );
+    // Residues larger than the modulus
+    assert_eq!(Ok((1, 5)), crt(&[(11, 5)]));
+    assert_eq!(
+        Err(CrtError::ZeroModulus { index: 1 }),
+        crt(&[(2, 3), (1, 0)])
+    );
+    // The result needs more than 64 bit
+    let big = u128::from(u64::MAX);
+    assert_eq!(
+        Ok((big - 1, big * (big - 2))),
+        crt(&[(big - 1, big), (1, big - 2)])
+    );
+    assert_eq!(
+        Err(CrtError::Overflow),
+        crt(&[(0, big), (0, big - 2), (0, big - 4)])
+    );
+    // Large non-coprime moduli, where the intermediate product does not fit into `u128`
+    let (p, q) = ((1_u128 << 61) - 1, (1_u128 << 64) - 59);
+    assert_eq!(
+        Ok((
+            71957972319735976195555471214764043673,
+            85070591730234615556860688623307063414
+        )),
+        crt(&[(12345678901234567, 2 * p), (9876543210987654321, 2 * q)])
+    );
+}
+
+#[test]
+fn test_mul_mod_u128() {
+    let big = i128::MAX as u128;
+    assert_eq!(1, mul_mod_u128(big - 1, big - 1, big));
+    assert_eq!(6, mul_mod_u128(2, 3, 7));
+    assert_eq!(0, mul_mod_u128(5, 0, 7));
+}
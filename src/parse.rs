@@ -0,0 +1,96 @@
+//! Error reporting for the input generators
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// A malformed puzzle input, pointing to the offending text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u32,
+    /// 1-based line number in the puzzle input
+    pub line: usize,
+    /// 1-based column in characters
+    pub column: usize,
+    /// The offending text
+    pub text: String,
+    pub reason: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day{} line {} column {}: {} '{}'",
+            self.day, self.line, self.column, self.reason, self.text
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The whole puzzle input of a day, used to create [`ParseError`]s
+///
+/// The position of an error is derived from where the offending text is located in the input.
+/// Thus, the offending text must be a sub-slice of the input, as returned by `lines`, `split`, etc.
+#[derive(Copy, Clone)]
+pub struct ParseContext<'a> {
+    day: u32,
+    input: &'a str,
+}
+
+impl<'a> ParseContext<'a> {
+    pub fn new(day: u32, input: &'a str) -> Self {
+        ParseContext { day, input }
+    }
+
+    /// Create an error pointing at `text`
+    pub fn error(&self, text: &str, reason: impl Display) -> ParseError {
+        let offset = (text.as_ptr() as usize).wrapping_sub(self.input.as_ptr() as usize);
+        let (line, column) = if offset <= self.input.len() {
+            let before = &self.input[..offset];
+            let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+            (
+                before.matches('\n').count() + 1,
+                before[line_start..].chars().count() + 1,
+            )
+        } else {
+            (0, 0)
+        };
+        ParseError {
+            day: self.day,
+            line,
+            column,
+            text: text.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Parse `text` and point at it if it is invalid
+    pub fn parse<T>(&self, text: &str) -> Result<T, ParseError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        text.parse()
+            .map_err(|err| self.error(text, format!("invalid value ({})", err)))
+    }
+
+    /// Unwrap a value which is missing from the text `context`
+    pub fn require<T>(&self, value: Option<T>, context: &str, what: &str) -> Result<T, ParseError> {
+        value.ok_or_else(|| self.error(context, format!("missing {} in", what)))
+    }
+}
+
+#[test]
+fn test_error_position() {
+    let input = "F10\nN3\nQ7";
+    let ctx = ParseContext::new(12, input);
+    let line = input.lines().nth(2).unwrap();
+    let err = ctx.error(&line[0..1], "unknown action");
+    assert_eq!((3, 1), (err.line, err.column));
+    assert_eq!("day12 line 3 column 1: unknown action 'Q'", err.to_string());
+
+    let err = ctx.parse::<u32>(&input[4..6]).unwrap_err();
+    assert_eq!((2, 1), (err.line, err.column));
+    assert_eq!("N3", err.text);
+}
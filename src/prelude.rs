@@ -1,5 +1,6 @@
 #![allow(unused_imports)]
 
+pub use crate::parse::{ParseContext, ParseError};
 pub use itertools::Itertools as _;
 pub use rayon::prelude::*;
 pub use recap::{Recap, Regex};
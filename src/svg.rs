@@ -0,0 +1,99 @@
+//! Minimal writer for SVG drawings
+//!
+//! Points are given with the y axis pointing up, as in the puzzles, and flipped when written.
+
+use std::fmt::Write;
+
+/// A drawing of a fixed area, which is built shape by shape
+pub struct Svg {
+    out: String,
+    /// Radius of markers, relative to the size of the drawing
+    marker_radius: f64,
+}
+
+impl Svg {
+    /// A drawing containing all points between `min` and `max`, `width` pixels wide
+    pub fn new(min: (i32, i32), max: (i32, i32), width: u32) -> Self {
+        let size_x = f64::from(max.0 - min.0).max(1.);
+        let size_y = f64::from(max.1 - min.1).max(1.);
+        let margin = size_x.max(size_y) / 20.;
+        let (view_width, view_height) = (size_x + 2. * margin, size_y + 2. * margin);
+        let height = (f64::from(width) * view_height / view_width).round();
+
+        let mut out = String::new();
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+            width,
+            height,
+            f64::from(min.0) - margin,
+            -f64::from(max.1) - margin,
+            view_width,
+            view_height,
+        )
+        .unwrap();
+        Svg {
+            out,
+            marker_radius: margin / 4.,
+        }
+    }
+
+    /// Connect all points with straight lines
+    pub fn polyline(&mut self, points: &[(i32, i32)], color: &str, title: &str) {
+        let points = points
+            .iter()
+            .map(|&(x, y)| format!("{},{}", x, -y))
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(
+            self.out,
+            r#"  <polyline points="{}" fill="none" stroke="{}" stroke-width="2" vector-effect="non-scaling-stroke"><title>{}</title></polyline>"#,
+            points,
+            escape(color),
+            escape(title)
+        )
+        .unwrap();
+    }
+
+    /// Mark a single point with a filled circle
+    pub fn marker(&mut self, (x, y): (i32, i32), color: &str, title: &str) {
+        writeln!(
+            self.out,
+            r#"  <circle cx="{}" cy="{}" r="{}" fill="{}"><title>{}</title></circle>"#,
+            x,
+            -y,
+            self.marker_radius,
+            escape(color),
+            escape(title)
+        )
+        .unwrap();
+    }
+
+    pub fn finish(mut self) -> String {
+        self.out.push_str("</svg>\n");
+        self.out
+    }
+}
+
+/// Escape text for use in XML content and attributes
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[test]
+fn test_svg() {
+    let mut svg = Svg::new((0, -10), (20, 10), 100);
+    svg.polyline(&[(0, 0), (20, 10), (20, -10)], "blue", "a < b");
+    svg.marker((0, 0), "green", "start");
+    assert_eq!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100" viewBox="-1 -11 22 22">
+  <polyline points="0,0 20,-10 20,10" fill="none" stroke="blue" stroke-width="2" vector-effect="non-scaling-stroke"><title>a &lt; b</title></polyline>
+  <circle cx="0" cy="0" r="0.25" fill="green"><title>start</title></circle>
+</svg>
+"#,
+        svg.finish()
+    );
+}
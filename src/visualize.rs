@@ -0,0 +1,56 @@
+//! Write the visualizations of a day into files
+//!
+//! Day 11 and day 17 are exported as text logs and animated GIFs, the routes of day 12 as SVG,
+//! the bag rules of day 7 and the message rules of day 19 as Graphviz graphs,
+//! and some of the adapter arrangements of day 10 as text.
+
+use std::error::Error;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Export all visualizations of `day` into `dir` and return the paths of the written files
+pub fn export(day: u32, input: &str, dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let (extension, files) = match day {
+        7 => ("dot", crate::day07::graphs(input).map_err(invalid_input)?),
+        10 => (
+            "txt",
+            crate::day10::arrangements(input).map_err(invalid_input)?,
+        ),
+        12 => ("svg", crate::day12::routes(input).map_err(invalid_input)?),
+        19 => ("dot", crate::day19::graphs(input).map_err(invalid_input)?),
+        11 | 17 => {
+            let animations = if day == 11 {
+                crate::day11::animations(input)
+            } else {
+                crate::day17::animations(input)
+            }
+            .map_err(invalid_input)?;
+            std::fs::create_dir_all(dir)?;
+            let mut paths = Vec::new();
+            for (name, animation) in animations {
+                let path = dir.join(format!("day{:02}_{}", day, name));
+                paths.extend(animation.save(&path, 4, 20)?);
+            }
+            return Ok(paths);
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("day {} has no visualization", day),
+            ))
+        }
+    };
+
+    std::fs::create_dir_all(dir)?;
+    let mut paths = Vec::new();
+    for (name, contents) in files {
+        let path = dir.join(format!("day{:02}_{}.{}", day, name, extension));
+        std::fs::write(&path, contents)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+fn invalid_input(err: impl Into<Box<dyn Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}